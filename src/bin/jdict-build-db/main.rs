@@ -1,54 +1,117 @@
-use std::io::{BufReader, Read};
+use std::io::BufReader;
 use std::path::Path;
 
+use jdict2::component_index::ComponentIndex;
 use jdict2::database::Database;
+use jdict2::jmnedict::NameDictionary;
+use jdict2::parse_error::{ParseContext, ParseError, ParseMode};
+use jdict2::phonetic_series::PhoneticIndex;
+use jdict2::search_index::SearchIndex;
 
 fn main() {
-    let db = load_from_source();
+    // Unknown tags and values are skipped and reported, unless --strict is passed
+    let mode = match std::env::args().any(|arg| arg == "--strict") {
+        true => ParseMode::Strict,
+        false => ParseMode::Lenient,
+    };
+    let db = load_from_source(mode);
     std::fs::write("./res/database.blob", postcard::to_allocvec(&db).unwrap()).unwrap();
 }
 
-pub fn load_from_source() -> Database {
-    pub fn load_gzip_xml(path: impl AsRef<Path>, buffer: &mut Vec<u8>) -> roxmltree::Document<'_> {
-        buffer.clear();
-
-        flate2::read::GzDecoder::new(BufReader::new(std::fs::File::open(path.as_ref()).unwrap()))
-            .read_to_end(buffer)
-            .unwrap();
-
-        roxmltree::Document::parse_with_options(
-            unsafe { std::str::from_utf8_unchecked(buffer) },
-            roxmltree::ParsingOptions {
-                allow_dtd: true,
-                ..Default::default()
-            },
-        )
-        .unwrap()
+type SourceReader = BufReader<flate2::bufread::GzDecoder<BufReader<std::fs::File>>>;
+
+/// Stream one gzipped source file through a parser, printing a summary of everything that was skipped.
+/// Exits the process if the parser fails, since there is no sensible database without it.
+fn parse_source<T>(
+    path: &str,
+    mode: ParseMode,
+    parse: impl FnOnce(SourceReader, &mut ParseContext) -> Result<T, ParseError>,
+) -> T {
+    let file = std::fs::File::open(path).unwrap_or_else(|error| {
+        eprintln!("{path}: {error}");
+        std::process::exit(1);
+    });
+    let reader = BufReader::new(flate2::bufread::GzDecoder::new(BufReader::new(file)));
+
+    let mut ctx = ParseContext::new(mode);
+    let result = parse(reader, &mut ctx);
+
+    if !ctx.skipped.is_empty() {
+        println!("{path}: skipped {} unexpected nodes", ctx.skipped.len());
+        for error in ctx.skipped.iter().take(10) {
+            println!("    {error}");
+        }
+        if ctx.skipped.len() > 10 {
+            println!("    ...");
+        }
     }
 
-    let mut buffer = Vec::new();
-    let dictionary = jdict2::jmdict::parsing::parse_jmdict(
-        load_gzip_xml("./res/JMdict_e.gz", &mut buffer).root_element(),
+    result.unwrap_or_else(|error| {
+        eprintln!("{path}: {error}");
+        std::process::exit(1);
+    })
+}
+
+pub fn load_from_source(mode: ParseMode) -> Database {
+    // Either JMdict_e or the multilingual JMdict
+    let jmdict_path = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .unwrap_or_else(|| "./res/JMdict_e.gz".to_string());
+
+    let mut dictionary = parse_source(
+        &jmdict_path,
+        mode,
+        jdict2::jmdict::parsing::parse_jmdict_stream,
     );
+    dictionary.sort_by_key(|entry| entry.ent_seq);
+    jdict2::jmdict::resolve_cross_references(&mut dictionary);
+
+    let search_index = SearchIndex::build(&dictionary);
 
-    let (_, kanji_dictionary) = jdict2::kanjidic2::parse_kanjidic2(
-        load_gzip_xml("./res/kanjidic2.xml.gz", &mut buffer).root_element(),
+    // Proper names are optional, because JMnedict is big and not needed for most words
+    let names = if Path::new("./res/JMnedict.xml.gz").exists() {
+        let entries = parse_source(
+            "./res/JMnedict.xml.gz",
+            mode,
+            jdict2::jmnedict::parse_jmnedict_stream,
+        );
+        Some(NameDictionary::new(entries))
+    }
+    else {
+        println!("./res/JMnedict.xml.gz not found, building without proper names");
+        None
+    };
+
+    let (_, kanji_dictionary) = parse_source(
+        "./res/kanjidic2.xml.gz",
+        mode,
+        jdict2::kanjidic2::parse_kanjidic2_stream,
     );
-    let kanji_dictionary = kanji_dictionary
+    let kanji_dictionary: egui::ahash::HashMap<_, _> = kanji_dictionary
         .into_iter()
         .map(|entry| (entry.unicode, entry))
         .collect();
 
-    let kanji_strokes = jdict2::kanjivg::parse_kanjivg(
-        load_gzip_xml("./res/kanjivg.xml.gz", &mut buffer).root_element(),
+    let kanji_strokes: egui::ahash::HashMap<_, _> = parse_source(
+        "./res/kanjivg.xml.gz",
+        mode,
+        jdict2::kanjivg::parse_kanjivg_stream,
     )
     .into_iter()
     .map(|entry| (entry.element.unwrap(), entry))
     .collect();
 
+    let component_index = ComponentIndex::build(&kanji_strokes);
+    let phonetic_index = PhoneticIndex::build(&kanji_strokes, &kanji_dictionary);
+
     Database {
         dictionary,
+        search_index,
+        names,
         kanji_dictionary,
         kanji_strokes,
+        component_index,
+        phonetic_index,
     }
 }
@@ -0,0 +1,136 @@
+use jdict2::database::Database;
+use jdict2::kanjivg::{self, DiagramStyle};
+
+const USAGE: &str = "\
+Usage: jdict-cli <command> [options]
+
+Commands:
+    stroke-order <kanji> <output.svg|output.png> [--size <pixels>] [--columns <count>] [--transparent]
+        Write a stroke order diagram with one panel per stroke
+
+Options:
+    --database <path>    Database built by jdict-build-db (default: ./res/database.blob)";
+
+fn main() {
+    let mut args = Args(std::env::args().skip(1).collect());
+    let database_path = args
+        .option("--database")
+        .unwrap_or_else(|| "./res/database.blob".to_string());
+
+    match args.positional().as_deref() {
+        Some("stroke-order") => stroke_order(&mut args, &database_path),
+        Some(command) => usage_error(&format!("unknown command {command}")),
+        None => usage_error("missing command"),
+    }
+}
+
+fn stroke_order(args: &mut Args, database_path: &str) {
+    let mut style = DiagramStyle::default();
+    if let Some(size) = args.option("--size") {
+        style.panel_size = parse_number(&size, "--size");
+    }
+    if let Some(columns) = args.option("--columns") {
+        style.columns = parse_number(&columns, "--columns");
+    }
+    if args.flag("--transparent") {
+        style.background = None;
+    }
+
+    let Some(kanji) = args.positional()
+    else {
+        usage_error("missing kanji");
+    };
+    let Some(output) = args.positional()
+    else {
+        usage_error("missing output file");
+    };
+    args.finish();
+
+    let mut chars = kanji.chars();
+    let (Some(kanji), None) = (chars.next(), chars.next())
+    else {
+        usage_error(&format!("expected a single kanji, got {kanji}"));
+    };
+
+    let png = match output
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+    {
+        Some(ext) if ext == "svg" => false,
+        Some(ext) if ext == "png" => true,
+        _ => usage_error(&format!("{output}: expected a .svg or .png file")),
+    };
+
+    let database = load_database(database_path);
+    let Some(strokes) = database.kanji_strokes.get(&kanji)
+    else {
+        eprintln!("No stroke order for {kanji}");
+        std::process::exit(1);
+    };
+
+    let data = match png {
+        false => kanjivg::stroke_order_svg(strokes, &style).map(String::into_bytes),
+        true => kanjivg::stroke_order_png(strokes, &style),
+    };
+    let data = data.unwrap_or_else(|error| {
+        eprintln!("{output}: {error}");
+        std::process::exit(1);
+    });
+    std::fs::write(&output, data).unwrap_or_else(|error| {
+        eprintln!("{output}: {error}");
+        std::process::exit(1);
+    });
+}
+
+fn load_database(path: &str) -> Database {
+    let blob = std::fs::read(path).unwrap_or_else(|error| {
+        eprintln!("{path}: {error}, build it with jdict-build-db first");
+        std::process::exit(1);
+    });
+    Database::load(&blob).unwrap_or_else(|error| {
+        eprintln!("{path}: {error}");
+        std::process::exit(1);
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str, option: &str) -> T {
+    value
+        .parse()
+        .unwrap_or_else(|_| usage_error(&format!("{option}: expected a number, got {value}")))
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{message}\n\n{USAGE}");
+    std::process::exit(2);
+}
+
+/// The remaining command line arguments, consumed as they are recognized.
+struct Args(Vec<String>);
+impl Args {
+    /// Remove `--name <value>` and return the value.
+    fn option(&mut self, name: &str) -> Option<String> {
+        let idx = self.0.iter().position(|arg| arg == name)?;
+        if idx + 1 == self.0.len() {
+            usage_error(&format!("{name}: missing value"));
+        }
+        self.0.remove(idx);
+        Some(self.0.remove(idx))
+    }
+
+    fn flag(&mut self, name: &str) -> bool {
+        let idx = self.0.iter().position(|arg| arg == name);
+        idx.map(|idx| self.0.remove(idx)).is_some()
+    }
+
+    /// Remove the first argument that isn't an option.
+    fn positional(&mut self) -> Option<String> {
+        let idx = self.0.iter().position(|arg| !arg.starts_with("--"))?;
+        Some(self.0.remove(idx))
+    }
+
+    fn finish(&self) {
+        if let Some(arg) = self.0.first() {
+            usage_error(&format!("unexpected argument {arg}"));
+        }
+    }
+}
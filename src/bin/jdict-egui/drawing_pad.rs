@@ -0,0 +1,88 @@
+use egui::{Pos2, Vec2};
+use jdict2::kanjivg::Coord;
+
+/// A square to draw strokes on with the mouse, with guides dividing it in quarters like on practice paper.
+/// Strokes are in KanjiVG coordinates (0..1).
+#[derive(Default)]
+pub(crate) struct DrawingPad {
+    current: Vec<Coord>,
+}
+
+pub(crate) struct PadResponse {
+    /// Clipped to the pad, which is its clip rect
+    pub(crate) painter:  egui::Painter,
+    /// A new stroke was started this frame
+    pub(crate) started:  bool,
+    /// The stroke that was finished this frame
+    pub(crate) finished: Option<Vec<Coord>>,
+}
+
+impl DrawingPad {
+    /// Paint the background of the pad and, if `enabled`, track the stroke being drawn.
+    /// The stroke in progress is not painted, see [`DrawingPad::current`].
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, size: f32, enabled: bool) -> PadResponse {
+        let (rect, res) = ui.allocate_exact_size(Vec2::splat(size), egui::Sense::drag());
+        let to_coord = |pos: Pos2| {
+            let pos = (pos - rect.min) / rect.size();
+            Coord {
+                x: pos.x.clamp(0.0, 1.0),
+                y: pos.y.clamp(0.0, 1.0),
+            }
+        };
+
+        let mut started = false;
+        let mut finished = None;
+        if enabled {
+            if let Some(pos) = res.interact_pointer_pos() {
+                if res.drag_started() {
+                    self.current.clear();
+                    started = true;
+                }
+                if res.dragged() || res.drag_started() {
+                    let coord = to_coord(pos);
+                    if self.current.last() != Some(&coord) {
+                        self.current.push(coord);
+                    }
+                }
+            }
+            if res.drag_stopped() && !self.current.is_empty() {
+                finished = Some(std::mem::take(&mut self.current));
+            }
+        }
+
+        let visuals = &ui.style().visuals;
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(
+            rect,
+            visuals.widgets.noninteractive.corner_radius,
+            visuals.extreme_bg_color,
+        );
+        let guide = egui::Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.3));
+        painter.hline(rect.x_range(), rect.center().y, guide);
+        painter.vline(rect.center().x, rect.y_range(), guide);
+
+        PadResponse {
+            painter,
+            started,
+            finished,
+        }
+    }
+
+    /// The stroke being drawn right now.
+    pub(crate) fn current(&self) -> &[Coord] { &self.current }
+}
+
+/// Paint a drawn stroke, as a dot if it is a single click.
+pub(crate) fn draw_stroke(painter: &egui::Painter, points: &[Coord], pen: egui::Stroke) {
+    let rect = painter.clip_rect();
+    let to_screen = |c: &Coord| rect.min + Vec2::new(c.x, c.y) * rect.size();
+    match points {
+        [] => (),
+        [dot] => {
+            painter.circle_filled(to_screen(dot), pen.width / 2.0, pen.color);
+        }
+        points => {
+            painter.line(points.iter().map(to_screen).collect(), pen);
+        }
+    }
+}
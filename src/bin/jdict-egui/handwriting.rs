@@ -0,0 +1,95 @@
+use jdict2::database::Database;
+use jdict2::kanjivg::{Candidate, Coord, Recognizer};
+
+use crate::drawing_pad::{draw_stroke, DrawingPad};
+
+const PAD_SIZE: f32 = 200.0;
+const MAX_CANDIDATES: usize = 30;
+
+/// Draw a kanji with the mouse, stroke by stroke, and pick it from the recognized candidates.
+#[derive(Default)]
+pub(crate) struct HandwritingPad {
+    // Finished strokes, in KanjiVG coordinates (0..1)
+    strokes: Vec<Vec<Coord>>,
+    pad: DrawingPad,
+    candidates: Vec<Candidate>,
+    // Built on first use, since it resamples the strokes of every kanji
+    recognizer: Option<Recognizer>,
+}
+impl HandwritingPad {
+    /// Returns the kanji the user clicked on.
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, database: &Database) -> Option<char> {
+        let mut clicked = None;
+        ui.horizontal_top(|ui| {
+            ui.vertical(|ui| {
+                self.show_pad(ui, database);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.strokes.is_empty(), egui::Button::new("Undo"))
+                        .clicked()
+                    {
+                        self.strokes.pop();
+                        self.recognize(database);
+                    }
+                    if ui
+                        .add_enabled(!self.strokes.is_empty(), egui::Button::new("Clear"))
+                        .clicked()
+                    {
+                        self.strokes.clear();
+                        self.candidates.clear();
+                    }
+                    ui.label(format!("{} strokes", self.strokes.len()));
+                });
+            });
+
+            egui::ScrollArea::vertical()
+                .id_salt("handwriting_candidates")
+                .max_height(PAD_SIZE)
+                .show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for candidate in &self.candidates {
+                            if ui
+                                .button(egui::RichText::new(candidate.kanji.to_string()).size(24.0))
+                                .clicked()
+                            {
+                                clicked = Some(candidate.kanji);
+                            }
+                        }
+                    });
+                });
+        });
+        clicked
+    }
+
+    fn show_pad(&mut self, ui: &mut egui::Ui, database: &Database) {
+        let pad = self.pad.show(ui, PAD_SIZE, true);
+        if let Some(stroke) = pad.finished {
+            self.strokes.push(stroke);
+            self.recognize(database);
+        }
+
+        let brush = egui::Stroke::new(4.0, ui.style().visuals.strong_text_color());
+        for stroke in self
+            .strokes
+            .iter()
+            .map(Vec::as_slice)
+            .chain([self.pad.current()])
+        {
+            draw_stroke(&pad.painter, stroke, brush);
+        }
+    }
+
+    fn recognize(&mut self, database: &Database) {
+        let recognizer = self.recognizer.get_or_insert_with(|| {
+            // Only suggest kanji we can show information about, KanjiVG also has kana and symbols
+            Recognizer::new(
+                database
+                    .kanji_strokes
+                    .iter()
+                    .filter(|(c, _)| database.kanji_dictionary.contains_key(c))
+                    .map(|(c, strokes)| (*c, strokes)),
+            )
+        });
+        self.candidates = recognizer.recognize(&self.strokes, MAX_CANDIDATES);
+    }
+}
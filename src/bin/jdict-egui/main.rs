@@ -2,32 +2,72 @@
 #![windows_subsystem = "windows"]
 
 mod debounce;
+mod drawing_pad;
+mod handwriting;
 mod pagination;
+mod phonetic_series;
+mod practice;
+mod radical_picker;
 mod search_box;
+mod skip_lookup;
 mod stroke_animation;
 
 use std::mem::take;
 use std::sync::{Arc, OnceLock};
 use std::time::Instant;
 
-use egui::global_theme_preference_buttons;
+use egui::containers::menu::{MenuButton, MenuConfig};
+use egui::{global_theme_preference_buttons, PopupCloseBehavior};
 use itertools::Itertools;
+use jdict2::deinflection::Deinflection;
+use jdict2::dictionary_search::KanjiRanking;
 use jdict2::jmdict;
-use jdict2::kanjidic2::ReadingType;
+use jdict2::kanjidic2::{QueryCode, ReadingType};
+use jdict2::kanjivg::Position;
 
 static DICTIONARY: OnceLock<jdict2::database::Database> = OnceLock::new();
 
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum ResultTab {
+    #[default]
+    Words,
+    Names,
+    Kanji,
+}
+
 #[derive(Default)]
 struct App {
     show_settings: bool,
-    show_kanji:    bool,
+    show_kanji: bool,
+    show_skip: bool,
+    show_radicals: bool,
+    show_phonetic: bool,
+    show_handwriting: bool,
 
     search: search_box::SearchBox,
     pagination: pagination::Pagination,
     search_debounce: debounce::Debounce,
+    skip_lookup: skip_lookup::SkipLookup,
+    radical_picker: radical_picker::RadicalPicker,
+    phonetic_browser: phonetic_series::PhoneticBrowser,
+    handwriting: handwriting::HandwritingPad,
+    stroke_player: stroke_animation::StrokePlayer,
+    practice: practice::StrokePractice,
 
+    tab: ResultTab,
     results: Vec<(u32, u32)>,
+    name_results: Vec<(u32, u32)>,
+    deinflections: Vec<Deinflection>,
     kanji_results: Vec<char>,
+    // Direct matches of the query by reading or meaning, unlike kanji_results which come from the word results
+    kanji_search_results: Vec<char>,
+    kanji_ranking: KanjiRanking,
+    // Shown above the kanji of the current results, e.g. after picking it by SKIP code or components
+    selected_kanji: Option<char>,
+    // Filter of the "Kanji containing this" menu
+    containing_position: Option<Position>,
+    // Entry opened from a cross-reference, shown above the word results, with the index of the referenced sense
+    opened_entry: Option<(usize, Option<u32>)>,
 }
 impl eframe::App for App {
     fn ui(&mut self, ui: &mut egui::Ui, _frame: &mut eframe::Frame) {
@@ -50,6 +90,11 @@ impl eframe::App for App {
                         );
                     });
                 });
+            egui::CollapsingHeader::new("Languages")
+                .default_open(true)
+                .show_unindented(ui, |ui| {
+                    self.search.show_language_editor(ui);
+                });
             egui::CollapsingHeader::new("Weights")
                 .default_open(true)
                 .show_unindented(ui, |ui| {
@@ -62,15 +107,64 @@ impl eframe::App for App {
                 |ui| {
                     ui.toggle_value(&mut self.show_settings, "\u{2699}\u{FE0F}");
                     ui.toggle_value(&mut self.show_kanji, "事");
+                    ui.toggle_value(&mut self.show_skip, "SKIP");
+                    ui.toggle_value(&mut self.show_radicals, "部");
+                    ui.toggle_value(&mut self.show_phonetic, "音");
+                    ui.toggle_value(&mut self.show_handwriting, "✍");
                 },
                 |ui| {
-                    self.pagination.show_controls(ui, self.results.len());
+                    let result_count = match self.tab {
+                        ResultTab::Words => self.results.len(),
+                        ResultTab::Names => self.name_results.len(),
+                        ResultTab::Kanji => self.kanji_search_results.len(),
+                    };
+                    self.pagination.show_controls(ui, result_count);
                 },
                 |ui| {
                     self.search.show_searchbox(ui);
                 },
             );
         });
+        egui::Panel::top("skip_lookup").show_animated_inside(ui, self.show_skip, |ui| {
+            let Some(database) = DICTIONARY.get()
+            else {
+                return;
+            };
+            if let Some(kanji) = self.skip_lookup.show(ui, database) {
+                self.selected_kanji = Some(kanji);
+                self.show_kanji = true;
+            }
+        });
+        egui::Panel::top("radical_picker").show_animated_inside(ui, self.show_radicals, |ui| {
+            let Some(database) = DICTIONARY.get()
+            else {
+                return;
+            };
+            if let Some(kanji) = self.radical_picker.show(ui, database) {
+                self.selected_kanji = Some(kanji);
+                self.show_kanji = true;
+            }
+        });
+        egui::Panel::top("phonetic_series").show_animated_inside(ui, self.show_phonetic, |ui| {
+            let Some(database) = DICTIONARY.get()
+            else {
+                return;
+            };
+            if let Some(kanji) = self.phonetic_browser.show(ui, database) {
+                self.selected_kanji = Some(kanji);
+                self.show_kanji = true;
+            }
+        });
+        egui::Panel::top("handwriting").show_animated_inside(ui, self.show_handwriting, |ui| {
+            let Some(database) = DICTIONARY.get()
+            else {
+                return;
+            };
+            if let Some(kanji) = self.handwriting.show(ui, database) {
+                self.selected_kanji = Some(kanji);
+                self.show_kanji = true;
+            }
+        });
         egui::Panel::left("kanji").show_animated_inside(ui, self.show_kanji, |ui| {
             ui.set_width(250.0);
 
@@ -83,27 +177,54 @@ impl eframe::App for App {
                 return;
             };
             egui::ScrollArea::vertical().show(ui, |ui| {
-                for character in &self.kanji_results {
-                    let info = &database.kanji_dictionary[character];
-                    let strokes = &database.kanji_strokes[character];
+                let selected = self
+                    .selected_kanji
+                    .filter(|c| !self.kanji_results.contains(c));
+                let mut navigate = None;
+                for character in selected.iter().chain(&self.kanji_results) {
+                    let Some(info) = database.kanji_dictionary.get(character)
+                    else {
+                        continue;
+                    };
+                    let strokes = database.kanji_strokes.get(character);
+                    let mut lookup = None;
                     for rm in info.reading_meaning.iter() {
                         for rmg in rm.reading_meaning_groups.iter() {
                             ui.horizontal(|ui| {
-                                stroke_animation::kanji_stroke_animation(ui, 60.0, strokes);
+                                if let Some(strokes) = strokes {
+                                    stroke_animation::kanji_stroke_animation(ui, 60.0, strokes);
+                                }
                                 ui.vertical(|ui| {
                                     egui::Grid::new(("KanjiGrid", ui.next_auto_id()))
                                         .min_col_width(0.0)
                                         .num_columns(2)
                                         .show(ui, |ui| {
-                                            let kunyomi = rmg
+                                            let kunyomi: Vec<_> = rmg
                                                 .readings
                                                 .iter()
-                                                .filter(|r| r.typ == ReadingType::Kunyomi)
-                                                .map(|r| &r.value)
-                                                .join(", ");
+                                                .filter_map(|r| r.kun())
+                                                .collect();
                                             if !kunyomi.is_empty() {
                                                 ui.label("Kun");
-                                                ui.add(egui::Label::new(kunyomi).wrap());
+                                                ui.horizontal_wrapped(|ui| {
+                                                    ui.spacing_mut().item_spacing.x = 0.0;
+                                                    for (idx, kun) in kunyomi.iter().enumerate() {
+                                                        if idx > 0 {
+                                                            ui.label("、");
+                                                        }
+                                                        // Look up the word this reading is used in, unless it's only an affix
+                                                        let text = kun.with_kanji(info.unicode);
+                                                        if !kun.prefix && !kun.suffix {
+                                                            if ui.link(text).clicked() {
+                                                                lookup =
+                                                                    Some(kun.written(info.unicode));
+                                                            }
+                                                        }
+                                                        else {
+                                                            ui.label(text);
+                                                        }
+                                                    }
+                                                });
                                                 ui.end_row();
                                             }
 
@@ -132,7 +253,7 @@ impl eframe::App for App {
                             let meanings = rmg
                                 .meanings
                                 .iter()
-                                .filter(|m| m.lang == isolang::Language::Eng)
+                                .filter(|m| self.search.languages.contains(&m.lang))
                                 .map(|m| &m.text)
                                 .join(", ");
                             if !meanings.is_empty() {
@@ -144,17 +265,75 @@ impl eframe::App for App {
                         }
                     }
 
+                    render_kanji_metadata(ui, info, &mut lookup);
+                    if let Some(lookup) = lookup {
+                        self.search.text = lookup;
+                        self.search_debounce.trigger();
+                    }
+
                     ui.horizontal(|ui| {
                         if ui.button("Words with this kanji").clicked() {
                             self.search.text = character.to_string();
                             self.search_debounce.trigger();
                         }
-                        ui.menu_button("Decomposition", |ui| {
-                            ui.label("Not implemented");
-                        });
+                        if let Some(strokes) = strokes {
+                            interactive_menu_button(ui, "Stroke order", |ui| {
+                                self.stroke_player.show(ui, 200.0, *character, strokes);
+                            });
+                            interactive_menu_button(ui, "Practice", |ui| {
+                                self.practice.show(ui, *character, strokes);
+                            });
+                            ui.menu_button("Decomposition", |ui| {
+                                let decomposition = strokes.decomposition();
+                                for component in &decomposition.children {
+                                    render_component(
+                                        ui,
+                                        database,
+                                        strokes,
+                                        component,
+                                        &mut navigate,
+                                    );
+                                }
+                            });
+                        }
+                        if !database
+                            .component_index
+                            .kanji_containing(*character)
+                            .is_empty()
+                        {
+                            ui.menu_button("Kanji containing this", |ui| {
+                                render_kanji_containing(
+                                    ui,
+                                    database,
+                                    *character,
+                                    &mut self.containing_position,
+                                    &mut navigate,
+                                );
+                            });
+                        }
+                        if database
+                            .phonetic_index
+                            .series_of(*character)
+                            .next()
+                            .is_some()
+                        {
+                            ui.menu_button("Phonetic series", |ui| {
+                                ui.set_max_width(300.0);
+                                for series in database.phonetic_index.series_of(*character) {
+                                    ui.strong(phonetic_series::series_title(series));
+                                    phonetic_series::render_series(ui, series, &mut navigate);
+                                }
+                                if navigate.is_some() {
+                                    ui.close();
+                                }
+                            });
+                        }
                     });
                     ui.separator();
                 }
+                if navigate.is_some() {
+                    self.selected_kanji = navigate;
+                }
             });
         });
         egui::CentralPanel::default().show_inside(ui, |ui| {
@@ -177,21 +356,138 @@ impl eframe::App for App {
                 jdict2::dictionary_search::search(
                     &self.search.text,
                     &self.search.search_weights,
+                    &self.search.languages,
                     &database.dictionary,
+                    &database.search_index,
                     &mut self.results,
                 );
+                self.deinflections = jdict2::dictionary_search::deinflect_query(&self.search.text);
+                self.opened_entry = None;
+                if let Some(names) = &database.names {
+                    jdict2::dictionary_search::search_names(
+                        &self.search.text,
+                        &self.search.search_weights,
+                        names,
+                        &mut self.name_results,
+                    );
+                }
+                self.search_kanji(database);
                 println!(
-                    "Found {} entries in {:?}",
+                    "Found {} entries, {} names and {} kanji in {:?}",
                     self.results.len(),
+                    self.name_results.len(),
+                    self.kanji_search_results.len(),
                     timer.elapsed()
                 );
             }
 
+            ui.horizontal(|ui| {
+                let tab = self.tab;
+                ui.selectable_value(
+                    &mut self.tab,
+                    ResultTab::Words,
+                    format!("Words ({})", self.results.len()),
+                );
+                if database.names.is_some() {
+                    ui.selectable_value(
+                        &mut self.tab,
+                        ResultTab::Names,
+                        format!("Names ({})", self.name_results.len()),
+                    );
+                }
+                ui.selectable_value(
+                    &mut self.tab,
+                    ResultTab::Kanji,
+                    format!("Kanji ({})", self.kanji_search_results.len()),
+                );
+                if tab != self.tab {
+                    self.pagination.page = 0;
+                    self.pagination.page_changed = true;
+                }
+            });
+            ui.separator();
+
+            match (self.tab, &database.names) {
+                (ResultTab::Names, Some(names)) => {
+                    self.pagination.show_entries(
+                        ui,
+                        &self.name_results,
+                        |ui, _, (entry_idx, _score)| {
+                            render_name(ui, &names.entries[*entry_idx as usize]);
+                            ui.separator();
+                        },
+                    );
+                    return;
+                }
+                (ResultTab::Kanji, _) => {
+                    ui.horizontal(|ui| {
+                        let ranking = self.kanji_ranking;
+                        ui.label("Sort by");
+                        ui.selectable_value(
+                            &mut self.kanji_ranking,
+                            KanjiRanking::Frequency,
+                            "Frequency",
+                        );
+                        ui.selectable_value(&mut self.kanji_ranking, KanjiRanking::Grade, "Grade");
+                        if ranking != self.kanji_ranking {
+                            self.search_kanji(database);
+                        }
+                    });
+                    let mut clicked = None;
+                    self.pagination
+                        .show_entries(ui, &self.kanji_search_results, |ui, _, c| {
+                            if let Some(character) = database.kanji_dictionary.get(c) {
+                                render_kanji_result(
+                                    ui,
+                                    character,
+                                    &self.search.languages,
+                                    &mut clicked,
+                                );
+                                ui.separator();
+                            }
+                        });
+                    if clicked.is_some() {
+                        self.selected_kanji = clicked;
+                        self.show_kanji = true;
+                    }
+                    return;
+                }
+                _ => (),
+            }
+
+            let mut open = None;
+            if let Some((entry_idx, sense)) = self.opened_entry {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.weak("Cross-reference");
+                        if ui.small_button("✖").on_hover_text("Close").clicked() {
+                            self.opened_entry = None;
+                        }
+                    });
+                    render_entry(
+                        ui,
+                        &database.dictionary[entry_idx],
+                        &[],
+                        &self.search.languages,
+                        sense,
+                        &mut open,
+                    );
+                });
+                ui.separator();
+            }
+
             self.kanji_results.clear();
             self.pagination
                 .show_entries(ui, &self.results, |ui, _, (entry_idx, _score)| {
                     let entry = &database.dictionary[*entry_idx as usize];
-                    let entry_visible = render_entry(ui, entry);
+                    let entry_visible = render_entry(
+                        ui,
+                        entry,
+                        &self.deinflections,
+                        &self.search.languages,
+                        None,
+                        &mut open,
+                    );
                     ui.separator();
 
                     if entry_visible {
@@ -204,10 +500,35 @@ impl eframe::App for App {
                         }
                     }
                 });
+
+            if let Some((seq, sense)) = open {
+                if let Some(entry_idx) = database.entry_index_by_seq(seq) {
+                    self.opened_entry = Some((entry_idx, sense));
+                }
+            }
         });
     }
 }
 
+impl App {
+    fn search_kanji(&mut self, database: &jdict2::database::Database) {
+        jdict2::dictionary_search::search_kanji(
+            &self.search.text,
+            &self.search.languages,
+            self.kanji_ranking,
+            &database.kanji_dictionary,
+            &mut self.kanji_search_results,
+        );
+    }
+}
+
+/// A menu button whose popup only closes when clicking outside of it, for menus with controls in them.
+fn interactive_menu_button(ui: &mut egui::Ui, title: &str, content: impl FnOnce(&mut egui::Ui)) {
+    MenuButton::new(title)
+        .config(MenuConfig::new().close_behavior(PopupCloseBehavior::CloseOnClickOutside))
+        .ui(ui, content);
+}
+
 fn triptichon_layout(
     ui: &mut egui::Ui,
     left: impl FnOnce(&mut egui::Ui),
@@ -268,7 +589,14 @@ fn triptichon_layout(
     ]));
 }
 
-fn render_entry(ui: &mut egui::Ui, entry: &jmdict::Entry) -> bool {
+fn render_entry(
+    ui: &mut egui::Ui,
+    entry: &jmdict::Entry,
+    deinflections: &[Deinflection],
+    languages: &[isolang::Language],
+    highlight_sense: Option<u32>,
+    open: &mut Option<(jmdict::EntrySeq, Option<u32>)>,
+) -> bool {
     let mut visible = false;
 
     ui.horizontal(|ui| {
@@ -291,26 +619,413 @@ fn render_entry(ui: &mut egui::Ui, entry: &jmdict::Entry) -> bool {
         }
     });
 
-    for sense in &entry.sense {
+    if let Some(deinflection) = deinflections.iter().find(|d| d.matches(entry)) {
+        ui.weak(deinflection.chain());
+    }
+
+    for (sense_idx, sense) in entry.sense.iter().enumerate() {
+        let glosses = sense
+            .glosses
+            .iter()
+            .filter(|gloss| languages.contains(&gloss.lang))
+            .collect_vec();
+        if glosses.is_empty() {
+            continue;
+        }
+
+        let part_of_speech = sense
+            .tags
+            .iter()
+            .filter_map(|tag| match tag {
+                jmdict::SenseTag::PartOfSpeech(pos) => Some(pos.to_string()),
+                _ => None,
+            })
+            .join(", ");
+        if !part_of_speech.is_empty() {
+            ui.weak(part_of_speech);
+        }
+
         ui.horizontal(|ui| {
             let mut text = " • ".to_string();
-            for (i, gloss) in sense.glosses.iter().enumerate() {
+            for (i, gloss) in glosses.iter().enumerate() {
                 if i != 0 {
                     text.push_str(", ");
                 }
                 text.push_str(&gloss.text);
             }
-            ui.label(text);
+            match highlight_sense == Some(sense_idx as u32) {
+                true => ui.label(
+                    egui::RichText::new(text)
+                        .strong()
+                        .background_color(ui.visuals().selection.bg_fill),
+                ),
+                false => ui.label(text),
+            };
         });
+
+        for (label, references) in [
+            (
+                "See also",
+                sense
+                    .tags
+                    .iter()
+                    .filter_map(|tag| match tag {
+                        jmdict::SenseTag::SeeAlso(xref) => Some(xref),
+                        _ => None,
+                    })
+                    .collect_vec(),
+            ),
+            (
+                "Antonym",
+                sense
+                    .tags
+                    .iter()
+                    .filter_map(|tag| match tag {
+                        jmdict::SenseTag::Antonym(xref) => Some(xref),
+                        _ => None,
+                    })
+                    .collect_vec(),
+            ),
+        ] {
+            if references.is_empty() {
+                continue;
+            }
+            ui.horizontal_wrapped(|ui| {
+                ui.weak(format!("   {label}:"));
+                for xref in references {
+                    match xref.target {
+                        Some(target) => {
+                            if ui.link(xref.to_string()).clicked() {
+                                *open = Some((target, xref.sense));
+                            }
+                        }
+                        None => {
+                            ui.weak(xref.to_string());
+                        }
+                    }
+                }
+            });
+        }
     }
 
     visible
 }
 
+fn render_name(ui: &mut egui::Ui, entry: &jdict2::jmnedict::NameEntry) {
+    ui.horizontal(|ui| {
+        for kanji in &entry.kanji {
+            ui.label(kanji.as_str());
+        }
+    });
+
+    ui.horizontal(|ui| {
+        for reading in &entry.reading {
+            ui.label(reading.as_str());
+        }
+    });
+
+    for translation in &entry.translations {
+        let name_types = translation
+            .name_types
+            .iter()
+            .map(|name_type| name_type.to_string())
+            .join(", ");
+        if !name_types.is_empty() {
+            ui.weak(name_types);
+        }
+        ui.label(format!(" • {}", translation.details.join(", ")));
+    }
+}
+
+/// One row of the kanji results tab: the kanji, which opens it in the kanji panel, with its readings and meanings.
+fn render_kanji_result(
+    ui: &mut egui::Ui,
+    character: &jdict2::kanjidic2::Character,
+    languages: &[isolang::Language],
+    clicked: &mut Option<char>,
+) {
+    ui.horizontal(|ui| {
+        if ui
+            .button(egui::RichText::new(character.unicode.to_string()).size(32.0))
+            .clicked()
+        {
+            *clicked = Some(character.unicode);
+        }
+        ui.vertical(|ui| {
+            let readings = || {
+                character
+                    .reading_meaning
+                    .iter()
+                    .flat_map(|rm| &rm.reading_meaning_groups)
+                    .flat_map(|rmg| &rmg.readings)
+            };
+            let onyomi = readings()
+                .filter(|r| matches!(r.typ, ReadingType::Onyomi(_)))
+                .map(|r| &r.value)
+                .join(", ");
+            let kunyomi = readings()
+                .filter_map(|r| r.kun())
+                .map(|kun| kun.with_kanji(character.unicode))
+                .join(", ");
+            ui.label(
+                [onyomi, kunyomi]
+                    .into_iter()
+                    .filter(|r| !r.is_empty())
+                    .join(" / "),
+            );
+
+            let meanings = character
+                .reading_meaning
+                .iter()
+                .flat_map(|rm| &rm.reading_meaning_groups)
+                .flat_map(|rmg| &rmg.meanings)
+                .filter(|m| languages.contains(&m.lang))
+                .map(|m| &m.text)
+                .join(", ");
+            ui.add(egui::Label::new(egui::RichText::new(meanings).size(16.0)).wrap());
+
+            let misc = &character.misc;
+            let details = [
+                misc.grade.map(|grade| format!("Grade {grade}")),
+                misc.jlpt.map(|jlpt| format!("Old JLPT {jlpt}")),
+                misc.frequency
+                    .map(|frequency| format!("#{frequency} in newspapers")),
+            ];
+            let details = details.into_iter().flatten().join(", ");
+            if !details.is_empty() {
+                ui.weak(details);
+            }
+        });
+    });
+}
+
+fn render_kanji_metadata(
+    ui: &mut egui::Ui,
+    character: &jdict2::kanjidic2::Character,
+    lookup: &mut Option<String>,
+) {
+    let misc = &character.misc;
+    egui::Grid::new(("KanjiMisc", ui.next_auto_id()))
+        .min_col_width(0.0)
+        .num_columns(2)
+        .show(ui, |ui| {
+            if let Some(stroke_count) = misc.stroke_count() {
+                ui.label("Strokes");
+                ui.horizontal(|ui| {
+                    ui.label(stroke_count.to_string());
+                    if !misc.miscounts().is_empty() {
+                        ui.weak(format!(
+                            "(often miscounted as {})",
+                            misc.miscounts().iter().join(", ")
+                        ));
+                    }
+                });
+                ui.end_row();
+            }
+
+            if let Some(grade) = misc.grade {
+                ui.label("Grade");
+                ui.label(match grade {
+                    1..=6 => format!("Kyōiku, grade {grade}"),
+                    8 => "Jōyō, secondary school".to_string(),
+                    9 | 10 => "Jinmeiyō".to_string(),
+                    _ => grade.to_string(),
+                });
+                ui.end_row();
+            }
+
+            if let Some(jlpt) = misc.jlpt {
+                ui.label("JLPT");
+                ui.label(format!("Level {jlpt} (pre-2010)"));
+                ui.end_row();
+            }
+
+            if let Some(frequency) = misc.frequency {
+                ui.label("Frequency");
+                ui.label(format!("#{frequency}"));
+                ui.end_row();
+            }
+
+            if !misc.radical_names.is_empty() {
+                ui.label("Radical");
+                ui.add(egui::Label::new(misc.radical_names.join(", ")).wrap());
+                ui.end_row();
+            }
+
+            let variants: Vec<char> = misc.variants.iter().filter_map(|v| v.character()).collect();
+            if !variants.is_empty() {
+                ui.label("Variants");
+                ui.horizontal_wrapped(|ui| {
+                    for variant in variants {
+                        if ui.link(variant.to_string()).clicked() {
+                            *lookup = Some(variant.to_string());
+                        }
+                    }
+                })
+                .response
+                .on_hover_text(
+                    misc.variants
+                        .iter()
+                        .map(|v| format!("{}: {}", v.typ, v.value))
+                        .join("\n"),
+                );
+                ui.end_row();
+            }
+
+            let skip = character.skip_codes().join(", ");
+            if !skip.is_empty() {
+                ui.label("SKIP");
+                ui.label(skip);
+                ui.end_row();
+            }
+        });
+
+    if !character.dic_number.is_empty() {
+        egui::CollapsingHeader::new("References")
+            .id_salt(("KanjiReferences", character.unicode))
+            .show(ui, |ui| {
+                egui::Grid::new(("KanjiReferencesGrid", character.unicode))
+                    .min_col_width(0.0)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for reference in &character.dic_number {
+                            ui.weak(reference.dictionary.to_string());
+                            match (reference.volume, reference.page) {
+                                (Some(volume), Some(page)) => ui.label(format!(
+                                    "{} (vol. {volume}, p. {page})",
+                                    reference.index
+                                )),
+                                _ => ui.label(reference.index.as_str()),
+                            };
+                            ui.end_row();
+                        }
+                        for code in &character.query_code {
+                            let (name, value) = match code {
+                                QueryCode::Skip(_) | QueryCode::SkipMisclassification(..) => {
+                                    continue;
+                                }
+                                QueryCode::SpahnHadamitzky(desc) => {
+                                    ("Spahn-Hadamitzky", desc.to_string())
+                                }
+                                QueryCode::FourCorner(code) => ("Four corner", code.to_string()),
+                                QueryCode::DeRoo(code) => ("De Roo", code.to_string()),
+                            };
+                            ui.weak(name);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
+
+/// One row of the decomposition menu: the kanji with the component's strokes highlighted, followed by its subcomponents.
+fn render_component(
+    ui: &mut egui::Ui,
+    database: &jdict2::database::Database,
+    kanji: &jdict2::kanjivg::StrokeGroup,
+    component: &jdict2::kanjivg::Component,
+    navigate: &mut Option<char>,
+) {
+    let element = component
+        .element
+        .filter(|c| database.kanji_dictionary.contains_key(c));
+
+    ui.horizontal(|ui| {
+        let highlight =
+            stroke_animation::kanji_highlighted_strokes(ui, 40.0, kanji, &component.strokes);
+        let label = component.element.map(String::from).unwrap_or_default();
+        let clicked = match element {
+            Some(_) => ui.link(egui::RichText::new(label).size(20.0)).clicked(),
+            None => {
+                ui.label(egui::RichText::new(label).size(20.0));
+                false
+            }
+        };
+        if (clicked || highlight.clicked()) && element.is_some() {
+            *navigate = element;
+            ui.close();
+        }
+
+        let mut details = Vec::new();
+        if let Some(original) = component.original {
+            details.push(format!("form of {original}"));
+        }
+        if let Some(position) = component.position {
+            details.push(position.to_string());
+        }
+        if component.radical.is_some() {
+            details.push("radical".to_string());
+        }
+        if component.phonetic.is_some() {
+            details.push("phonetic".to_string());
+        }
+        if !details.is_empty() {
+            ui.weak(details.join(", "));
+        }
+    });
+
+    if !component.children.is_empty() {
+        ui.indent(ui.next_auto_id(), |ui| {
+            for child in &component.children {
+                render_component(ui, database, kanji, child, navigate);
+            }
+        });
+    }
+}
+
+/// Kanji using `component`, optionally only those where it's at a given position, e.g. 氵 on the left.
+fn render_kanji_containing(
+    ui: &mut egui::Ui,
+    database: &jdict2::database::Database,
+    component: char,
+    position: &mut Option<Position>,
+    navigate: &mut Option<char>,
+) {
+    let index = &database.component_index;
+    let positions = index.positions_of(component);
+    if position.is_some_and(|p| !positions.contains(&p)) {
+        *position = None;
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        ui.selectable_value(position, None, "Anywhere");
+        for p in positions {
+            ui.selectable_value(position, Some(p), p.to_string());
+        }
+    });
+
+    let mut kanji: Vec<char> = index
+        .kanji_containing_at(component, *position)
+        .iter()
+        .copied()
+        .filter(|c| *c != component)
+        .collect();
+    database.sort_kanji_by_frequency(&mut kanji);
+
+    ui.label(format!("{} kanji", kanji.len()));
+    egui::ScrollArea::vertical()
+        .max_height(300.0)
+        .show(ui, |ui| {
+            ui.set_max_width(300.0);
+            ui.horizontal_wrapped(|ui| {
+                for c in kanji {
+                    if ui
+                        .button(egui::RichText::new(c.to_string()).size(20.0))
+                        .clicked()
+                    {
+                        *navigate = Some(c);
+                        ui.close();
+                    }
+                }
+            });
+        });
+}
+
 fn main() {
     std::thread::spawn(|| {
         DICTIONARY.get_or_init(|| {
-            postcard::from_bytes(include_bytes!("../../../res/database.blob")).unwrap()
+            jdict2::database::Database::load(include_bytes!("../../../res/database.blob")).unwrap()
         });
     });
 
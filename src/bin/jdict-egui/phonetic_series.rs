@@ -0,0 +1,85 @@
+use jdict2::database::Database;
+use jdict2::phonetic_series::PhoneticSeries;
+
+/// Browse kanji grouped by their phonetic component, to see which readings a component suggests.
+#[derive(Default)]
+pub(crate) struct PhoneticBrowser {
+    // Kanji or reading to filter the series by
+    filter: String,
+}
+impl PhoneticBrowser {
+    /// Returns the kanji the user clicked on.
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, database: &Database) -> Option<char> {
+        let series = database.phonetic_index.series();
+
+        ui.horizontal(|ui| {
+            ui.label("Filter");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.filter)
+                    .hint_text("Kanji or on'yomi")
+                    .desired_width(120.0),
+            );
+            ui.label(format!("{} series", series.len()));
+        });
+
+        let filter = self.filter.trim();
+        let mut clicked = None;
+        egui::ScrollArea::vertical()
+            .id_salt("phonetic_series")
+            .max_height(250.0)
+            .show(ui, |ui| {
+                for series in series.iter().filter(|s| matches_filter(s, filter)) {
+                    egui::CollapsingHeader::new(series_title(series))
+                        .id_salt(("phonetic_series", series.phonetic))
+                        .show(ui, |ui| render_series(ui, series, &mut clicked));
+                }
+            });
+        clicked
+    }
+}
+
+/// The members of a series with their on'yomi, highlighting those that don't follow the series' reading.
+pub(crate) fn render_series(
+    ui: &mut egui::Ui,
+    series: &PhoneticSeries,
+    clicked: &mut Option<char>,
+) {
+    ui.horizontal_wrapped(|ui| {
+        for member in &series.members {
+            let mut text =
+                egui::RichText::new(format!("{} {}", member.kanji, member.onyomi.join("・")));
+            if member.irregular {
+                text = text.color(ui.visuals().warn_fg_color);
+            }
+            let mut button = ui.button(text);
+            if member.irregular {
+                button = button.on_hover_text("Reading departs from the series");
+            }
+            if button.clicked() {
+                *clicked = Some(member.kanji);
+            }
+        }
+    });
+}
+
+pub(crate) fn series_title(series: &PhoneticSeries) -> String {
+    match series.readings.is_empty() {
+        true => format!("{} ({})", series.phonetic, series.members.len()),
+        false => format!(
+            "{} {} ({})",
+            series.phonetic,
+            series.readings.join("・"),
+            series.members.len()
+        ),
+    }
+}
+
+fn matches_filter(series: &PhoneticSeries, filter: &str) -> bool {
+    filter.is_empty()
+        || filter.contains(series.phonetic)
+        || series.readings.iter().any(|r| r == filter)
+        || series
+            .members
+            .iter()
+            .any(|m| filter.contains(m.kanji) || m.onyomi.iter().any(|r| r == filter))
+}
@@ -0,0 +1,158 @@
+use jdict2::kanjivg::{Coord, StrokeChecker, StrokeFeedback, StrokeGroup};
+
+use crate::drawing_pad::{draw_stroke, DrawingPad};
+use crate::stroke_animation::draw_path;
+
+const PAD_SIZE: f32 = 200.0;
+// Seconds to draw a hinted stroke of the kanji's full size
+const HINT_SECONDS: f32 = 1.5;
+
+/// Trace a kanji stroke by stroke, with feedback on the order, direction and shape of every stroke.
+#[derive(Default)]
+pub(crate) struct StrokePractice {
+    kanji: Option<char>,
+    checker: Option<StrokeChecker>,
+    // Every attempt at each stroke that was drawn correctly so far, plus the current one
+    attempts: Vec<Vec<StrokeFeedback>>,
+    pad: DrawingPad,
+    // The last attempt if it was wrong, shown until the next one starts
+    rejected: Option<Vec<Coord>>,
+    show_outline: bool,
+    // Time at which the hint animation for the next stroke started
+    hint_start: Option<f64>,
+}
+impl StrokePractice {
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, character: char, kanji: &StrokeGroup) {
+        if self.kanji != Some(character) {
+            *self = Self {
+                kanji: Some(character),
+                checker: Some(StrokeChecker::new(kanji)),
+                show_outline: self.show_outline,
+                ..Default::default()
+            };
+        }
+        let Some(checker) = &self.checker
+        else {
+            return;
+        };
+        let stroke_count = checker.stroke_count();
+        let done = self
+            .attempts
+            .iter()
+            .filter(|a| a.last() == Some(&StrokeFeedback::Correct))
+            .count();
+
+        ui.horizontal(|ui| {
+            if ui.button("Restart").clicked() {
+                self.attempts.clear();
+                self.rejected = None;
+                self.hint_start = None;
+            }
+            if ui
+                .add_enabled(done < stroke_count, egui::Button::new("Hint"))
+                .on_hover_text("Show how to draw the next stroke")
+                .clicked()
+            {
+                self.hint_start = Some(ui.ctx().input(|i| i.time));
+            }
+            ui.checkbox(&mut self.show_outline, "Outline");
+            ui.label(format!("{done}/{stroke_count}"));
+        });
+
+        let pad = self.pad.show(ui, PAD_SIZE, done < stroke_count);
+        if pad.started {
+            self.rejected = None;
+        }
+        if let Some(drawn) = pad.finished {
+            let feedback = checker.check(done, &drawn);
+            if self.attempts.len() == done {
+                self.attempts.push(Vec::new());
+            }
+            self.attempts[done].push(feedback);
+            if feedback == StrokeFeedback::Correct {
+                self.hint_start = None;
+            }
+            else {
+                self.rejected = Some(drawn);
+            }
+        }
+        let done = self
+            .attempts
+            .iter()
+            .filter(|a| a.last() == Some(&StrokeFeedback::Correct))
+            .count();
+
+        let visuals = ui.style().visuals.clone();
+        let painter = pad.painter;
+
+        let outline = egui::Stroke::new(
+            PAD_SIZE / 30.0,
+            visuals.weak_text_color().gamma_multiply(0.3),
+        );
+        let brush = egui::Stroke::new(PAD_SIZE / 30.0, visuals.strong_text_color());
+        let hint = egui::Stroke::new(PAD_SIZE / 30.0, visuals.selection.stroke.color);
+        for (idx, stroke) in kanji.strokes().enumerate() {
+            let (brush, mut budget) = if idx < done {
+                (brush, f32::INFINITY)
+            }
+            else if let Some(hint_start) = self.hint_start.filter(|_| idx == done) {
+                let elapsed = ui.ctx().input(|i| i.time) - hint_start;
+                // Replay the hint whenever it finished, after a short pause
+                let progress = (elapsed as f32 / HINT_SECONDS) % 1.5;
+                ui.ctx().request_repaint();
+                (hint, progress)
+            }
+            else if self.show_outline {
+                (outline, f32::INFINITY)
+            }
+            else {
+                continue;
+            };
+            draw_path(&painter, &stroke.path, brush, &mut budget);
+        }
+
+        let pen = egui::Stroke::new(4.0, visuals.text_color());
+        let rejected_pen = egui::Stroke::new(4.0, visuals.error_fg_color);
+        draw_stroke(&painter, self.pad.current(), pen);
+        if let Some(rejected) = &self.rejected {
+            draw_stroke(&painter, rejected, rejected_pen);
+        }
+
+        if let Some(feedback) = self.attempts.get(done).and_then(|a| a.last()) {
+            let message = match feedback {
+                StrokeFeedback::Correct => String::new(),
+                StrokeFeedback::Reversed => format!("Stroke {} goes the other way", done + 1),
+                StrokeFeedback::WrongOrder(stroke) => {
+                    format!(
+                        "That's stroke {}, draw stroke {} first",
+                        stroke + 1,
+                        done + 1
+                    )
+                }
+                StrokeFeedback::Mismatch => format!("That doesn't look like stroke {}", done + 1),
+            };
+            ui.colored_label(visuals.warn_fg_color, message);
+        }
+        else if done == stroke_count && stroke_count > 0 {
+            let mistakes = self.attempts.iter().filter(|a| a.len() > 1).count();
+            ui.label(match mistakes {
+                0 => "Perfect!".to_string(),
+                _ => format!("Done, with mistakes in {mistakes} strokes"),
+            });
+        }
+
+        // One mark per stroke: ✔ if it was right the first time, otherwise what went wrong first
+        ui.horizontal_wrapped(|ui| {
+            for (idx, attempts) in self.attempts.iter().enumerate() {
+                let (text, color) = match attempts.first() {
+                    Some(StrokeFeedback::Correct) => ("✔", visuals.text_color()),
+                    Some(StrokeFeedback::Reversed) => ("↺", visuals.warn_fg_color),
+                    Some(StrokeFeedback::WrongOrder(_)) => ("⇄", visuals.warn_fg_color),
+                    Some(StrokeFeedback::Mismatch) | None => ("✘", visuals.error_fg_color),
+                };
+                ui.colored_label(color, format!("{}{text}", idx + 1))
+                    .on_hover_text(format!("{} attempts", attempts.len()));
+            }
+        });
+    }
+}
@@ -0,0 +1,114 @@
+use egui::ahash::HashSet;
+use jdict2::database::Database;
+
+// Components that only appear in a handful of kanji don't help narrowing down the search
+const MIN_KANJI_PER_COMPONENT: usize = 5;
+
+/// Find kanji by picking the components they contain, like the radical search on jisho.org.
+#[derive(Default)]
+pub(crate) struct RadicalPicker {
+    selected:   Vec<char>,
+    // Components shown in the picker, sorted by stroke count
+    components: Vec<char>,
+    // Kanji containing all selected components, most common first
+    candidates: Vec<char>,
+    // Components of at least one candidate, i.e. those that can still be selected
+    available:  HashSet<char>,
+    changed:    bool,
+}
+impl RadicalPicker {
+    /// Returns the kanji the user clicked on.
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, database: &Database) -> Option<char> {
+        let index = &database.component_index;
+
+        if self.components.is_empty() {
+            self.components = index
+                .components()
+                .into_iter()
+                .filter(|c| index.kanji_containing(*c).len() >= MIN_KANJI_PER_COMPONENT)
+                .collect();
+            self.changed = true;
+        }
+
+        if std::mem::take(&mut self.changed) {
+            self.candidates.clear();
+            self.available.clear();
+            if self.selected.is_empty() {
+                self.available.extend(&self.components);
+            }
+            else {
+                self.candidates = index.kanji_containing_all(&self.selected);
+                database.sort_kanji_by_frequency(&mut self.candidates);
+                for kanji in &self.candidates {
+                    self.available.extend(index.components_of(*kanji));
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!self.selected.is_empty(), egui::Button::new("Reset"))
+                .clicked()
+            {
+                self.selected.clear();
+                self.changed = true;
+            }
+            if !self.selected.is_empty() {
+                ui.label(format!("{} kanji", self.candidates.len()));
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .id_salt("radical_picker_components")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    let mut stroke_count = None;
+                    for &component in &self.components {
+                        let strokes = index.stroke_count(component);
+                        if strokes != stroke_count {
+                            stroke_count = strokes;
+                            if let Some(strokes) = strokes {
+                                ui.strong(strokes.to_string());
+                            }
+                        }
+
+                        let selected = self.selected.contains(&component);
+                        let enabled = selected || self.available.contains(&component);
+                        let button = egui::Button::selectable(
+                            selected,
+                            egui::RichText::new(component.to_string()).size(18.0),
+                        );
+                        if ui.add_enabled(enabled, button).clicked() {
+                            match selected {
+                                true => self.selected.retain(|c| *c != component),
+                                false => self.selected.push(component),
+                            }
+                            self.changed = true;
+                        }
+                    }
+                });
+            });
+
+        let mut clicked = None;
+        if !self.candidates.is_empty() {
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .id_salt("radical_picker_candidates")
+                .max_height(150.0)
+                .show(ui, |ui| {
+                    ui.horizontal_wrapped(|ui| {
+                        for &c in &self.candidates {
+                            if ui
+                                .button(egui::RichText::new(c.to_string()).size(24.0))
+                                .clicked()
+                            {
+                                clicked = Some(c);
+                            }
+                        }
+                    });
+                });
+        }
+        clicked
+    }
+}
@@ -5,6 +5,7 @@ pub(crate) struct SearchBox {
     pub(crate) request_focus: bool,
     pub(crate) text: String,
     pub(crate) search_weights: jdict2::dictionary_search::SearchWeights,
+    pub(crate) languages: Vec<isolang::Language>,
 }
 impl Default for SearchBox {
     fn default() -> Self {
@@ -13,6 +14,7 @@ impl Default for SearchBox {
             request_focus: true,
             text: String::new(),
             search_weights: jdict2::dictionary_search::SearchWeights::default(),
+            languages: vec![isolang::Language::Eng],
         }
     }
 }
@@ -32,6 +34,21 @@ impl SearchBox {
         self.changed = search_box.changed();
     }
 
+    pub(crate) fn show_language_editor(&mut self, ui: &mut egui::Ui) {
+        for (language, name) in jdict2::jmdict::GLOSS_LANGUAGES {
+            let mut enabled = self.languages.contains(&language);
+            if ui.checkbox(&mut enabled, name).changed() {
+                if enabled {
+                    self.languages.push(language);
+                }
+                else {
+                    self.languages.retain(|l| *l != language);
+                }
+                self.changed = true;
+            }
+        }
+    }
+
     pub(crate) fn show_weight_editor(&mut self, ui: &mut egui::Ui) {
         egui::Grid::new("weights").show(ui, |ui| {
             ui.label("Kanji");
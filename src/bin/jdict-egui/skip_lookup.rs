@@ -0,0 +1,115 @@
+use jdict2::database::Database;
+use jdict2::kanjidic2::SkipCode;
+
+const PATTERNS: [(u8, &str); 4] = [
+    (1, "⿰ Left-right"),
+    (2, "⿱ Top-bottom"),
+    (3, "⿴ Enclosure"),
+    (4, "■ Solid"),
+];
+const SOLID_SUBPATTERNS: [(u8, &str); 4] = [
+    (1, "Top line"),
+    (2, "Bottom line"),
+    (3, "Through line"),
+    (4, "Other"),
+];
+
+/// Find kanji by their SKIP code, either typed as "1-4-3" or built from the pattern and stroke counts.
+pub(crate) struct SkipLookup {
+    pub(crate) skip: SkipCode,
+    pub(crate) misclassifications: bool,
+    text: String,
+    changed: bool,
+    results: Vec<char>,
+}
+impl Default for SkipLookup {
+    fn default() -> Self {
+        Self {
+            skip: SkipCode {
+                pattern: 1,
+                first:   1,
+                second:  1,
+            },
+            misclassifications: true,
+            text: "1-1-1".into(),
+            changed: true,
+            results: Vec::new(),
+        }
+    }
+}
+impl SkipLookup {
+    /// Returns the kanji the user clicked on.
+    pub(crate) fn show(&mut self, ui: &mut egui::Ui, database: &Database) -> Option<char> {
+        let skip = self.skip;
+
+        ui.horizontal(|ui| {
+            ui.label("SKIP");
+            let text = ui.add(egui::TextEdit::singleline(&mut self.text).desired_width(60.0));
+            if text.changed() {
+                if let Ok(skip) = self.text.parse() {
+                    self.skip = skip;
+                }
+            }
+
+            ui.separator();
+            for (pattern, name) in PATTERNS {
+                ui.selectable_value(&mut self.skip.pattern, pattern, name);
+            }
+
+            ui.separator();
+            ui.add(egui::DragValue::new(&mut self.skip.first).range(1..=30));
+            ui.label("-");
+            if self.skip.pattern == 4 {
+                self.skip.second = self.skip.second.clamp(1, 4);
+                egui::ComboBox::from_id_salt("skip_subpattern")
+                    .selected_text(SOLID_SUBPATTERNS[self.skip.second as usize - 1].1)
+                    .show_ui(ui, |ui| {
+                        for (subpattern, name) in SOLID_SUBPATTERNS {
+                            ui.selectable_value(&mut self.skip.second, subpattern, name);
+                        }
+                    });
+            }
+            else {
+                ui.add(egui::DragValue::new(&mut self.skip.second).range(1..=30));
+            }
+
+            ui.separator();
+            self.changed |= ui
+                .checkbox(
+                    &mut self.misclassifications,
+                    "Include common misclassifications",
+                )
+                .changed();
+        });
+
+        if self.skip != skip {
+            self.changed = true;
+            if self.text.parse::<SkipCode>() != Ok(self.skip) {
+                self.text = self.skip.to_string();
+            }
+        }
+
+        if std::mem::take(&mut self.changed) {
+            self.results = database.kanji_by_skip(self.skip, self.misclassifications);
+            database.sort_kanji_by_frequency(&mut self.results);
+        }
+
+        let mut clicked = None;
+        ui.label(format!("{} kanji", self.results.len()));
+        egui::ScrollArea::vertical()
+            .max_height(150.0)
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    for &c in &self.results {
+                        if ui
+                            .button(egui::RichText::new(c.to_string()).size(24.0))
+                            .clicked()
+                        {
+                            clicked = Some(c);
+                        }
+                    }
+                });
+            });
+        clicked
+    }
+}
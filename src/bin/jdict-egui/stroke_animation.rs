@@ -1,11 +1,14 @@
 use std::sync::LazyLock;
 use std::time::Instant;
 
-use egui::Vec2;
+use egui::{Pos2, Vec2};
 use jdict2::kanjivg::{self, Coord, StrokeGroup};
 
 static START_TIME: LazyLock<Instant> = LazyLock::new(Instant::now);
 
+// How far the drawn polylines may deviate from the actual curves, in pixels
+const TESSELLATION_TOLERANCE: f32 = 0.25;
+
 pub(crate) fn kanji_stroke_animation(ui: &mut egui::Ui, size: f32, kanji: &StrokeGroup) {
     let (rect, res) = ui.allocate_exact_size((size, size).into(), egui::Sense::hover());
 
@@ -26,126 +29,275 @@ pub(crate) fn kanji_stroke_animation(ui: &mut egui::Ui, size: f32, kanji: &Strok
         style.visuals.extreme_bg_color,
     );
 
-    draw_recursive(
-        &ui.painter_at(rect.shrink(3.0)),
-        kanji,
-        if f > 0.0 {
-            interacted_style.bg_stroke
+    let painter = ui.painter_at(rect.shrink(3.0));
+    for stroke in kanji.strokes() {
+        draw_path(
+            &painter,
+            &stroke.path,
+            if f > 0.0 {
+                interacted_style.bg_stroke
+            }
+            else {
+                interacted_style.fg_stroke
+            },
+            #[allow(const_item_mutation)]
+            &mut f32::INFINITY,
+        );
+    }
+
+    if f > 0.0 {
+        for stroke in kanji.strokes() {
+            draw_path(&painter, &stroke.path, interacted_style.fg_stroke, &mut f);
         }
+    }
+}
+
+fn measure(kanji: &StrokeGroup) -> f32 { kanji.strokes().map(|stroke| stroke.path.length()).sum() }
+
+/// Draw the first `length_budget` of the path, following the curves, and subtract what was drawn from the budget.
+pub(crate) fn draw_path(
+    painter: &egui::Painter,
+    path: &kanjivg::Path,
+    brush: egui::Stroke,
+    length_budget: &mut f32,
+) {
+    let rect = painter.clip_rect();
+    let to_screen = |c: &Coord| rect.min + Vec2::new(c.x, c.y) * rect.size();
+
+    for polyline in path.polylines(TESSELLATION_TOLERANCE / rect.width()) {
+        let Some(first) = polyline.first()
         else {
-            interacted_style.fg_stroke
-        },
-        #[allow(const_item_mutation)]
-        &mut f32::INFINITY,
+            continue;
+        };
+        if *length_budget <= 0.0 {
+            return;
+        }
+
+        let mut points = vec![to_screen(first)];
+        for segment in polyline.windows(2) {
+            let length = segment[0].distance(&segment[1]);
+            if length > *length_budget {
+                let t = *length_budget / length;
+                let end = Coord {
+                    x: segment[0].x + (segment[1].x - segment[0].x) * t,
+                    y: segment[0].y + (segment[1].y - segment[0].y) * t,
+                };
+                points.push(to_screen(&end));
+                *length_budget = 0.0;
+                break;
+            }
+            *length_budget -= length;
+            points.push(to_screen(&segment[1]));
+        }
+        painter.line(points, brush);
+    }
+}
+
+/// Where the stroke starts on screen, for placing its number.
+fn stroke_start(painter: &egui::Painter, path: &kanjivg::Path) -> Option<Pos2> {
+    let rect = painter.clip_rect();
+    path.0.iter().find_map(|cmd| match cmd {
+        kanjivg::Command::MoveTo(c) => Some(rect.min + Vec2::new(c.x, c.y) * rect.size()),
+        _ => None,
+    })
+}
+
+/// Draw a kanji with the strokes at the given indices (in stroke order) emphasized, e.g. to show where a component is.
+pub(crate) fn kanji_highlighted_strokes(
+    ui: &mut egui::Ui,
+    size: f32,
+    kanji: &StrokeGroup,
+    highlight: &[usize],
+) -> egui::Response {
+    let (rect, res) = ui.allocate_exact_size((size, size).into(), egui::Sense::click());
+
+    let style = ui.style();
+    let interacted_style = style.interact(&res);
+
+    ui.painter().rect_filled(
+        rect,
+        interacted_style.corner_radius,
+        style.visuals.extreme_bg_color,
     );
 
-    if f > 0.0 {
-        draw_recursive(
-            &ui.painter_at(rect.shrink(3.0)),
-            kanji,
-            interacted_style.fg_stroke,
-            &mut f,
+    let painter = ui.painter_at(rect.shrink(3.0));
+    let highlighted_brush = egui::Stroke::new(2.0, style.visuals.selection.stroke.color);
+    let brush = egui::Stroke::new(1.0, style.visuals.weak_text_color());
+    for (idx, stroke) in kanji.strokes().enumerate() {
+        let brush = match highlight.contains(&idx) {
+            true => highlighted_brush,
+            false => brush,
+        };
+        draw_path(
+            &painter,
+            &stroke.path,
+            brush,
+            #[allow(const_item_mutation)]
+            &mut f32::INFINITY,
         );
     }
 
-    fn measure(kanji: &StrokeGroup) -> f32 {
-        kanji
-            .subgroups
-            .iter()
-            .map(|child| match child {
-                kanjivg::Child::Group(group) => measure(group),
-                kanjivg::Child::Stroke(stroke) => stroke.path.length(),
-            })
-            .sum()
-    }
+    res
+}
 
-    fn draw_recursive(
-        painter: &egui::Painter,
-        path: &kanjivg::StrokeGroup,
-        brush: egui::Stroke,
-        length_budget: &mut f32,
-    ) {
-        for child in &path.subgroups {
-            match child {
-                kanjivg::Child::Stroke(stroke) => {
-                    draw_path(painter, &stroke.path, brush, length_budget);
-                }
-                kanjivg::Child::Group(group) => {
-                    draw_recursive(painter, group, brush, length_budget);
-                }
-            }
+/// A large stroke order animation with controls to pause, step through the strokes one by one and change the speed.
+pub(crate) struct StrokePlayer {
+    kanji: Option<char>,
+    playing: bool,
+    // Length of the strokes drawn so far
+    progress: f32,
+    // Kanji sizes per second
+    speed: f32,
+    show_numbers: bool,
+}
+impl Default for StrokePlayer {
+    fn default() -> Self {
+        Self {
+            kanji: None,
+            playing: true,
+            progress: 0.0,
+            speed: 0.5,
+            show_numbers: true,
         }
     }
-    fn draw_path(
-        painter: &egui::Painter,
-        path: &kanjivg::Path,
-        brush: egui::Stroke,
-        length_budget: &mut f32,
+}
+impl StrokePlayer {
+    pub(crate) fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        size: f32,
+        character: char,
+        kanji: &StrokeGroup,
     ) {
-        let mut brush_position = Vec2::new(0.0, 0.0);
-        for cmd in &path.0 {
-            match cmd {
-                kanjivg::Command::MoveTo(Coord { x, y }) => {
-                    brush_position = Vec2::new(*x, *y);
-                }
-                kanjivg::Command::LineTo(Coord { x, y }) => {
-                    // painter.line_segment(
-                    //     [
-                    //         offset + brush_position * scale,
-                    //         offset + Vec2::new(*x, *y) * scale,
-                    //     ],
-                    //     brush,
-                    // );
-                    // brush_position = Vec2::new(*x, *y);
-                    painter.add(take_line_segment(
-                        &painter.clip_rect(),
-                        brush_position,
-                        Vec2::new(*x, *y),
-                        brush,
-                        length_budget,
-                    ));
-                }
-                kanjivg::Command::CubicBezier(c1, c2, to) => {
-                    let c1 = Vec2::new(c1.x, c1.y);
-                    let c2 = Vec2::new(c2.x, c2.y);
-                    let to = Vec2::new(to.x, to.y);
-                    for (a, b) in [(brush_position, c1), (c1, c2), (c2, to)] {
-                        painter.add(take_line_segment(
-                            &painter.clip_rect(),
-                            a,
-                            b,
-                            brush,
-                            length_budget,
-                        ));
-                    }
-                    brush_position = to;
-                }
-                _ => eprintln!("Unimplented stroke type: {cmd:?}"),
-            }
+        if self.kanji != Some(character) {
+            self.kanji = Some(character);
+            self.progress = 0.0;
+            self.playing = true;
         }
 
-        fn take_line_segment(
-            rect: &egui::Rect,
-            from: Vec2,
-            to: Vec2,
-            stroke: egui::Stroke,
-            length_budget: &mut f32,
-        ) -> egui::Shape {
-            let length = (to - from).length();
-            if length > *length_budget {
-                *length_budget = 0.0;
-                return egui::Shape::Noop;
+        // Where each stroke ends, measured from the start of the first one
+        let stroke_ends: Vec<f32> = kanji
+            .strokes()
+            .scan(0.0, |length, stroke| {
+                *length += stroke.path.length();
+                Some(*length)
+            })
+            .collect();
+        let total = stroke_ends.last().copied().unwrap_or(0.0);
+        // Index of the stroke currently being drawn, or the number of strokes when done
+        let current_stroke = stroke_ends
+            .iter()
+            .position(|end| self.progress < *end)
+            .unwrap_or(stroke_ends.len());
+
+        ui.horizontal(|ui| {
+            let restart = ui.button("⏮").on_hover_text("Restart").clicked();
+            let step_back = ui
+                .add_enabled(self.progress > 0.0, egui::Button::new("⏴"))
+                .on_hover_text("Previous stroke")
+                .clicked();
+            let play_pause = ui
+                .button(if self.playing { "⏸" } else { "▶" })
+                .on_hover_text(if self.playing { "Pause" } else { "Play" })
+                .clicked();
+            let step_forward = ui
+                .add_enabled(self.progress < total, egui::Button::new("⏵"))
+                .on_hover_text("Next stroke")
+                .clicked();
+
+            if restart {
+                self.progress = 0.0;
+                self.playing = true;
+            }
+            if play_pause {
+                self.playing = !self.playing;
+                if self.playing && self.progress >= total {
+                    self.progress = 0.0;
+                }
+            }
+            if step_back {
+                self.playing = false;
+                // To the end of the stroke before the current one, or the one before that if we are exactly at its end
+                self.progress = stroke_ends[..current_stroke]
+                    .iter()
+                    .rev()
+                    .copied()
+                    .find(|end| *end < self.progress)
+                    .unwrap_or(0.0);
+            }
+            if step_forward {
+                self.playing = false;
+                self.progress = stroke_ends.get(current_stroke).copied().unwrap_or(total);
             }
 
-            let length = length.min(*length_budget);
-            *length_budget -= length;
+            ui.label(format!(
+                "{}/{}",
+                current_stroke.min(stroke_ends.len()),
+                stroke_ends.len()
+            ));
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::Slider::new(&mut self.speed, 0.1..=2.0)
+                    .logarithmic(true)
+                    .text("Speed"),
+            );
+            ui.checkbox(&mut self.show_numbers, "Numbers");
+        });
 
-            let to = from + (to - from).normalized() * length;
+        if self.playing {
+            self.progress += ui.ctx().input(|i| i.stable_dt) * self.speed;
+            if self.progress >= total {
+                self.progress = total;
+                self.playing = false;
+            }
+            ui.ctx().request_repaint();
+        }
 
-            let from = rect.min + from * rect.size();
-            let to = rect.min + to * rect.size();
+        let (rect, _) = ui.allocate_exact_size((size, size).into(), egui::Sense::hover());
+        let visuals = &ui.style().visuals;
+        ui.painter().rect_filled(
+            rect,
+            visuals.widgets.noninteractive.corner_radius,
+            visuals.extreme_bg_color,
+        );
 
-            egui::Shape::line_segment([from, to], stroke)
+        let painter = ui.painter_at(rect.shrink(size / 20.0));
+        let guide = egui::Stroke::new(size / 100.0, visuals.weak_text_color().gamma_multiply(0.5));
+        let brush = egui::Stroke::new(size / 30.0, visuals.strong_text_color());
+        let mut budget = self.progress;
+        for stroke in kanji.strokes() {
+            draw_path(
+                &painter,
+                &stroke.path,
+                guide,
+                #[allow(const_item_mutation)]
+                &mut f32::INFINITY,
+            );
+            draw_path(&painter, &stroke.path, brush, &mut budget);
+        }
+
+        if self.show_numbers {
+            let label_painter = ui.painter_at(rect);
+            let font = egui::FontId::proportional((size / 18.0).max(9.0));
+            for (idx, stroke) in kanji.strokes().enumerate() {
+                let Some(start) = stroke_start(&painter, &stroke.path)
+                else {
+                    continue;
+                };
+                let color = match idx == current_stroke {
+                    true => visuals.selection.stroke.color,
+                    false => visuals.weak_text_color(),
+                };
+                // Slightly up and to the left, so the number doesn't cover the stroke
+                label_painter.text(
+                    start - Vec2::splat(size / 40.0),
+                    egui::Align2::RIGHT_BOTTOM,
+                    (idx + 1).to_string(),
+                    font.clone(),
+                    color,
+                );
+            }
         }
     }
 }
@@ -0,0 +1,242 @@
+use egui::ahash::HashMap;
+use strum::IntoEnumIterator;
+
+use crate::kanjivg::{Position, StrokeGroup};
+
+/// Inverted index from components (the `element` attributes in KanjiVG) to the kanji containing them.
+/// Used to look up kanji by picking the radicals and other parts they are made of.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct ComponentIndex {
+    // Sorted kanji containing each component, including the component itself if it is a kanji
+    kanji: HashMap<char, Vec<char>>,
+    // Sorted components of each kanji
+    components: HashMap<char, Vec<char>>,
+    // Sorted kanji containing each component at a specific position, e.g. 氵 on the left
+    positioned: HashMap<(char, Position), Vec<char>>,
+    // Stroke count of each component
+    strokes: HashMap<char, u8>,
+}
+impl ComponentIndex {
+    pub fn build(kanji_strokes: &HashMap<char, StrokeGroup>) -> Self {
+        let mut index = ComponentIndex::default();
+
+        for (&kanji, strokes) in kanji_strokes {
+            let mut components: Vec<char> = strokes.groups().filter_map(|g| g.element).collect();
+            components.sort_unstable();
+            components.dedup();
+
+            for &component in &components {
+                index.kanji.entry(component).or_default().push(kanji);
+            }
+            index.components.insert(kanji, components);
+
+            let mut positioned: Vec<(char, Position)> = strokes
+                .groups()
+                .filter_map(|g| Some((g.element?, g.position?)))
+                .collect();
+            positioned.sort_unstable_by_key(|(c, p)| (*c, *p as u8));
+            positioned.dedup();
+            for key in positioned {
+                index.positioned.entry(key).or_default().push(kanji);
+            }
+            index
+                .strokes
+                .insert(kanji, clamp_u8(strokes.stroke_count()));
+        }
+
+        // Components that aren't kanji themselves take the stroke count of their largest occurrence
+        for strokes in kanji_strokes.values() {
+            for group in strokes.groups() {
+                let Some(element) = group.element
+                else {
+                    continue;
+                };
+                // Partial groups only contain some of the component's strokes
+                if kanji_strokes.contains_key(&element) || group.partial || group.part.is_some() {
+                    continue;
+                }
+                let count = index.strokes.entry(element).or_default();
+                *count = (*count).max(clamp_u8(group.stroke_count()));
+            }
+        }
+
+        for kanji in index
+            .kanji
+            .values_mut()
+            .chain(index.positioned.values_mut())
+        {
+            kanji.sort_unstable();
+            kanji.shrink_to_fit();
+        }
+
+        index
+    }
+
+    /// All components, sorted by stroke count.
+    pub fn components(&self) -> Vec<char> {
+        let mut components: Vec<char> = self.kanji.keys().copied().collect();
+        components.sort_unstable_by_key(|c| (self.stroke_count(*c).unwrap_or(u8::MAX), *c));
+        components
+    }
+
+    pub fn stroke_count(&self, component: char) -> Option<u8> {
+        self.strokes.get(&component).copied().filter(|c| *c > 0)
+    }
+
+    /// Kanji containing `component`, in codepoint order.
+    pub fn kanji_containing(&self, component: char) -> &[char] {
+        self.kanji
+            .get(&component)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Kanji containing `component` at `position`, in codepoint order, e.g. 氵 on the left.
+    /// Without a position, this is the same as [`ComponentIndex::kanji_containing`].
+    pub fn kanji_containing_at(&self, component: char, position: Option<Position>) -> &[char] {
+        let Some(position) = position
+        else {
+            return self.kanji_containing(component);
+        };
+        self.positioned
+            .get(&(component, position))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Positions at which `component` occurs in at least one kanji.
+    pub fn positions_of(&self, component: char) -> Vec<Position> {
+        Position::iter()
+            .filter(|p| self.positioned.contains_key(&(component, *p)))
+            .collect()
+    }
+
+    /// Components of `kanji`, in codepoint order.
+    pub fn components_of(&self, kanji: char) -> &[char] {
+        self.components
+            .get(&kanji)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Kanji containing every one of `components`, in codepoint order.
+    pub fn kanji_containing_all(&self, components: &[char]) -> Vec<char> {
+        let mut postings: Vec<&[char]> = components
+            .iter()
+            .map(|c| self.kanji_containing(*c))
+            .collect();
+        // Start with the rarest component, like SearchIndex::candidates
+        postings.sort_unstable_by_key(|p| p.len());
+        let Some((shortest, rest)) = postings.split_first()
+        else {
+            return Vec::new();
+        };
+
+        shortest
+            .iter()
+            .copied()
+            .filter(|kanji| rest.iter().all(|p| p.binary_search(kanji).is_ok()))
+            .collect()
+    }
+}
+
+fn clamp_u8(n: usize) -> u8 { n.min(u8::MAX as usize) as u8 }
+
+#[cfg(test)]
+mod test {
+    use egui::ahash::HashMap;
+
+    use super::ComponentIndex;
+    use crate::kanjivg::testing::strokes;
+    use crate::kanjivg::{Child, Position, StrokeGroup};
+
+    fn group(element: Option<char>, subgroups: Vec<Child>) -> StrokeGroup {
+        StrokeGroup {
+            element,
+            subgroups,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_kanji_containing_all() {
+        let kanji = HashMap::from_iter([
+            ('木', group(Some('木'), strokes(4))),
+            ('口', group(Some('口'), strokes(3))),
+            (
+                '林',
+                group(Some('林'), vec![
+                    group(Some('木'), strokes(4)).into(),
+                    group(Some('木'), strokes(4)).into(),
+                ]),
+            ),
+            (
+                '杏',
+                group(Some('杏'), vec![
+                    group(Some('木'), strokes(4)).into(),
+                    group(Some('口'), strokes(3)).into(),
+                ]),
+            ),
+        ]);
+        let index = ComponentIndex::build(&kanji);
+
+        assert_eq!(index.kanji_containing('木'), ['木', '杏', '林']);
+        assert_eq!(index.kanji_containing_all(&['木', '口']), ['杏']);
+        assert_eq!(index.kanji_containing_all(&['口']), ['口', '杏']);
+        assert_eq!(index.components_of('林'), ['木', '林']);
+        assert_eq!(index.stroke_count('林'), Some(8));
+        assert_eq!(index.stroke_count('木'), Some(4));
+        assert_eq!(index.components()[..2], ['口', '木']);
+    }
+
+    #[test]
+    fn test_kanji_containing_at() {
+        let at = |element, position, count| StrokeGroup {
+            position: Some(position),
+            ..group(Some(element), strokes(count))
+        };
+        let kanji = HashMap::from_iter([
+            ('口', group(Some('口'), strokes(3))),
+            (
+                '吐',
+                group(Some('吐'), vec![
+                    at('口', Position::Left, 3).into(),
+                    at('土', Position::Right, 3).into(),
+                ]),
+            ),
+            (
+                '知',
+                group(Some('知'), vec![
+                    at('矢', Position::Left, 5).into(),
+                    at('口', Position::Right, 3).into(),
+                ]),
+            ),
+            (
+                '杏',
+                group(Some('杏'), vec![
+                    at('木', Position::Top, 4).into(),
+                    at('口', Position::Bottom, 3).into(),
+                ]),
+            ),
+        ]);
+        let index = ComponentIndex::build(&kanji);
+
+        assert_eq!(index.kanji_containing_at('口', None), [
+            '口', '吐', '杏', '知'
+        ]);
+        assert_eq!(index.kanji_containing_at('口', Some(Position::Left)), [
+            '吐'
+        ]);
+        assert_eq!(index.kanji_containing_at('口', Some(Position::Right)), [
+            '知'
+        ]);
+        assert!(index
+            .kanji_containing_at('口', Some(Position::Tare))
+            .is_empty());
+        assert_eq!(index.positions_of('口'), [
+            Position::Bottom,
+            Position::Left,
+            Position::Right
+        ]);
+    }
+}
@@ -1,10 +1,193 @@
 use egui::ahash::HashMap;
 
-use crate::{jmdict, kanjidic2, kanjivg};
+use crate::component_index::ComponentIndex;
+use crate::phonetic_series::PhoneticIndex;
+use crate::search_index::SearchIndex;
+use crate::{jmdict, jmnedict, kanjidic2, kanjivg};
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Database {
+    // Sorted by ent_seq
     pub dictionary: Vec<jmdict::Entry>,
+    pub search_index: SearchIndex,
+    // Only present if JMnedict was available when building the database
+    pub names: Option<jmnedict::NameDictionary>,
     pub kanji_dictionary: HashMap<char, kanjidic2::Character>,
     pub kanji_strokes: HashMap<char, kanjivg::StrokeGroup>,
+    pub component_index: ComponentIndex,
+    pub phonetic_index: PhoneticIndex,
+}
+impl Database {
+    /// Deserialize a database built by jdict-build-db.
+    pub fn load(blob: &[u8]) -> Result<Database, postcard::Error> {
+        let database: Database = postcard::from_bytes(blob)?;
+        debug_assert!(
+            database.dictionary.is_sorted_by_key(|entry| entry.ent_seq),
+            "dictionary must be sorted by ent_seq"
+        );
+        Ok(database)
+    }
+
+    /// Find an entry by its JMdict sequence number.
+    pub fn entry_by_seq(&self, seq: jmdict::EntrySeq) -> Option<&jmdict::Entry> {
+        self.entry_index_by_seq(seq)
+            .map(|idx| &self.dictionary[idx])
+    }
+
+    /// Position of an entry in [`Database::dictionary`], which is sorted by sequence number, so this is a binary search.
+    /// Never finds [`jmdict::EntrySeq::INVALID`].
+    pub fn entry_index_by_seq(&self, seq: jmdict::EntrySeq) -> Option<usize> {
+        if seq == jmdict::EntrySeq::INVALID {
+            return None;
+        }
+        self.dictionary
+            .binary_search_by_key(&seq, |entry| entry.ent_seq)
+            .ok()
+    }
+
+    /// All kanji with the given SKIP code, in codepoint order.
+    /// With `misclassifications`, this also finds kanji that are commonly misclassified as this code.
+    pub fn kanji_by_skip(&self, skip: kanjidic2::SkipCode, misclassifications: bool) -> Vec<char> {
+        self.find_kanji(|character| character.has_skip_code(skip, misclassifications))
+    }
+
+    /// All kanji with the given query code (SKIP, Spahn-Hadamitzky, four-corner or De Roo), in codepoint order.
+    pub fn kanji_by_query_code(&self, code: &kanjidic2::QueryCode) -> Vec<char> {
+        self.find_kanji(|character| character.query_code.contains(code))
+    }
+
+    /// All kanji listed under `index` in the given dictionary, e.g. Heisig number 1024.
+    pub fn kanji_by_dictionary_index(
+        &self,
+        dictionary: kanjidic2::Dictionary,
+        index: &str,
+    ) -> Vec<char> {
+        self.find_kanji(|character| {
+            character
+                .dic_number
+                .iter()
+                .any(|r| r.dictionary == dictionary && r.index == index)
+        })
+    }
+
+    /// Sort kanji from most to least common, by newspaper frequency and then stroke count.
+    /// Kanji without a frequency rank come last.
+    pub fn sort_kanji_by_frequency(&self, kanji: &mut [char]) {
+        kanji.sort_by_key(|c| {
+            let misc = self
+                .kanji_dictionary
+                .get(c)
+                .map(|character| &character.misc);
+            (
+                misc.and_then(|misc| misc.frequency).unwrap_or(u16::MAX),
+                misc.and_then(|misc| misc.stroke_count()).unwrap_or(u8::MAX),
+            )
+        });
+    }
+
+    fn find_kanji(&self, predicate: impl Fn(&kanjidic2::Character) -> bool) -> Vec<char> {
+        let mut result: Vec<char> = self
+            .kanji_dictionary
+            .values()
+            .filter(|character| predicate(character))
+            .map(|character| character.unicode)
+            .collect();
+        result.sort_unstable();
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Database;
+    use crate::jmdict::testing::entry;
+    use crate::jmdict::{Entry, EntrySeq};
+    use crate::kanjidic2::testing::character;
+    use crate::kanjidic2::{Character, Dictionary, QueryCode, SkipMisclassification};
+
+    fn database(dictionary: Vec<Entry>, kanji: Vec<Character>) -> Database {
+        Database {
+            dictionary,
+            search_index: Default::default(),
+            names: None,
+            kanji_dictionary: kanji
+                .into_iter()
+                .map(|character| (character.unicode, character))
+                .collect(),
+            kanji_strokes: Default::default(),
+            component_index: Default::default(),
+            phonetic_index: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_entry_by_seq() {
+        let database = database(
+            vec![
+                entry(1000220, "明白", "めいはく", "obvious"),
+                entry(1358280, "食べる", "たべる", "to eat"),
+                entry(1577980, "", "ごちそうさま", "thank you for the meal"),
+            ],
+            Vec::new(),
+        );
+
+        assert_eq!(database.entry_index_by_seq(EntrySeq(1358280)), Some(1));
+        assert_eq!(
+            database
+                .entry_by_seq(EntrySeq(1577980))
+                .map(|entry| entry.reading[0].text.as_str()),
+            Some("ごちそうさま")
+        );
+        assert_eq!(database.entry_index_by_seq(EntrySeq(1358281)), None);
+        assert!(database.entry_by_seq(EntrySeq(0)).is_none());
+        assert_eq!(database.entry_index_by_seq(EntrySeq::INVALID), None);
+    }
+
+    #[test]
+    fn test_kanji_lookup() {
+        let skip = "1-4-3".parse().unwrap();
+        let database = database(Vec::new(), vec![
+            character('社')
+                .frequency(21)
+                .stroke_count(7)
+                .query_code(QueryCode::Skip(skip))
+                .dic_ref(Dictionary::Heisig, "1128"),
+            character('杜')
+                .stroke_count(7)
+                .query_code(QueryCode::Skip(skip)),
+            character('灯')
+                .frequency(1283)
+                .stroke_count(6)
+                .query_code(QueryCode::Skip("1-4-2".parse().unwrap()))
+                .query_code(QueryCode::SkipMisclassification(
+                    skip,
+                    SkipMisclassification::StrokeCount,
+                ))
+                .dic_ref(Dictionary::Heisig, "166"),
+            character('村')
+                .frequency(219)
+                .stroke_count(7)
+                .query_code(QueryCode::Skip(skip)),
+            character('汁').frequency(1283).stroke_count(5),
+        ]);
+
+        // In codepoint order
+        assert_eq!(database.kanji_by_skip(skip, false), ['村', '杜', '社']);
+        assert_eq!(database.kanji_by_skip(skip, true), ['村', '杜', '灯', '社']);
+        assert_eq!(database.kanji_by_query_code(&QueryCode::Skip(skip)), [
+            '村', '杜', '社'
+        ]);
+        assert_eq!(
+            database.kanji_by_dictionary_index(Dictionary::Heisig, "166"),
+            ['灯']
+        );
+        assert!(database
+            .kanji_by_dictionary_index(Dictionary::Heisig6, "166")
+            .is_empty());
+
+        // Most frequent first, fewer strokes first among equally frequent kanji, and kanji without a frequency last
+        let mut kanji = ['杜', '灯', '村', '汁', '社'];
+        database.sort_kanji_by_frequency(&mut kanji);
+        assert_eq!(kanji, ['社', '村', '汁', '灯', '杜']);
+    }
 }
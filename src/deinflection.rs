@@ -0,0 +1,405 @@
+//! Rule based deinflection of conjugated verbs and adjectives.
+//!
+//! Rules are applied repeatedly from the end of the word, so 食べなかった becomes 食べない (past) and then 食べる (negative).
+//! Every rule records the word class of the form it produces, and a rule only applies to forms of a matching class.
+//! This prevents chains that don't exist in the language, like treating the た of 食べた as an adjective.
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::jmdict::{self, PartOfSpeech, SenseTag};
+
+/// A set of word classes, used to decide which rules can follow each other and which entries a candidate can match.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WordClasses(u16);
+impl WordClasses {
+    pub const NONE: WordClasses = WordClasses(0);
+    pub const ICHIDAN: WordClasses = WordClasses(1 << 0);
+    pub const GODAN: WordClasses = WordClasses(1 << 1);
+    pub const KURU: WordClasses = WordClasses(1 << 2);
+    pub const SURU: WordClasses = WordClasses(1 << 3);
+    /// Nouns taking the auxiliary verb suru, like 勉強
+    pub const SURU_NOUN: WordClasses = WordClasses(1 << 4);
+    pub const ADJ_I: WordClasses = WordClasses(1 << 5);
+
+    // Intermediate forms, which can be conjugated further but are never dictionary forms
+    const TE: WordClasses = WordClasses(1 << 8);
+    const PAST: WordClasses = WordClasses(1 << 9);
+    const MASU: WordClasses = WordClasses(1 << 10);
+
+    pub fn is_empty(&self) -> bool { self.0 == 0 }
+    pub fn intersects(&self, other: WordClasses) -> bool { self.0 & other.0 != 0 }
+
+    /// The classes of a part of speech tag. Parts of speech that don't conjugate map to [`WordClasses::NONE`].
+    pub fn from_part_of_speech(pos: PartOfSpeech) -> WordClasses {
+        use PartOfSpeech::*;
+        match pos {
+            V1 | V1S => WordClasses::ICHIDAN,
+            V5aru | V5b | V5g | V5k | V5kS | V5m | V5n | V5r | V5rI | V5s | V5t | V5u | V5uS
+            | V5uru => WordClasses::GODAN,
+            Vk => WordClasses::KURU,
+            VsI | VsS => WordClasses::SURU,
+            Vs => WordClasses::SURU_NOUN,
+            AdjI | AdjIx => WordClasses::ADJ_I,
+            _ => WordClasses::NONE,
+        }
+    }
+
+    /// All classes an entry belongs to, across all of its senses.
+    pub fn of_entry(entry: &jmdict::Entry) -> WordClasses {
+        entry
+            .sense
+            .iter()
+            .flat_map(|sense| &sense.tags)
+            .filter_map(|tag| match tag {
+                SenseTag::PartOfSpeech(pos) => Some(WordClasses::from_part_of_speech(*pos)),
+                _ => None,
+            })
+            .fold(WordClasses::NONE, |a, b| a | b)
+    }
+}
+impl std::ops::BitOr for WordClasses {
+    type Output = WordClasses;
+    fn bitor(self, rhs: Self) -> WordClasses { WordClasses(self.0 | rhs.0) }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display)]
+pub enum Inflection {
+    #[strum(serialize = "negative")]
+    Negative,
+    #[strum(serialize = "past")]
+    Past,
+    #[strum(serialize = "-te")]
+    Te,
+    #[strum(serialize = "polite")]
+    Polite,
+    #[strum(serialize = "progressive")]
+    Progressive,
+    #[strum(serialize = "potential")]
+    Potential,
+    #[strum(serialize = "passive")]
+    Passive,
+    #[strum(serialize = "potential or passive")]
+    PotentialOrPassive,
+    #[strum(serialize = "causative")]
+    Causative,
+    #[strum(serialize = "imperative")]
+    Imperative,
+    #[strum(serialize = "volitional")]
+    Volitional,
+    #[strum(serialize = "-ba")]
+    Provisional,
+    #[strum(serialize = "-tara")]
+    Conditional,
+    #[strum(serialize = "-tai")]
+    Desire,
+    #[strum(serialize = "adverb")]
+    Adverbial,
+    #[strum(serialize = "noun")]
+    Noun,
+}
+
+/// A candidate dictionary form of a conjugated word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deinflection {
+    pub text: String,
+    /// Word classes the dictionary form can belong to
+    pub word_class: WordClasses,
+    /// The inflections leading from the dictionary form to the input, outermost last.
+    pub inflections: Vec<Inflection>,
+}
+impl Deinflection {
+    /// The rule chain in human readable form, e.g. "negative → past".
+    pub fn chain(&self) -> String {
+        let mut result = String::new();
+        for (i, inflection) in self.inflections.iter().enumerate() {
+            if i != 0 {
+                result.push_str(" → ");
+            }
+            result.push_str(&inflection.to_string());
+        }
+        result
+    }
+
+    /// Whether the entry has this dictionary form as a kanji or reading, and a part of speech allowing the inflections.
+    pub fn matches(&self, entry: &jmdict::Entry) -> bool {
+        let text_matches = entry.kanji.iter().any(|k| k.text == self.text)
+            || entry.reading.iter().any(|r| r.text == self.text);
+
+        text_matches && WordClasses::of_entry(entry).intersects(self.word_class)
+    }
+}
+
+/// Produce every candidate dictionary form of `text`. The input itself is not part of the result.
+/// ```
+/// use jdict2::deinflection::{deinflect, WordClasses};
+///
+/// let candidates = deinflect("食べなかった");
+/// let taberu = candidates.iter().find(|d| d.text == "食べる").unwrap();
+/// assert_eq!(taberu.word_class, WordClasses::ICHIDAN);
+/// assert_eq!(taberu.chain(), "negative → past");
+///
+/// assert!(deinflect("高くない")
+///     .iter()
+///     .any(|d| d.text == "高い" && d.word_class == WordClasses::ADJ_I));
+/// assert!(deinflect("書きました")
+///     .iter()
+///     .any(|d| d.text == "書く" && d.chain() == "polite → past"));
+/// ```
+pub fn deinflect(text: &str) -> Vec<Deinflection> {
+    let mut result = Vec::<Deinflection>::new();
+    let mut seen = HashSet::<(String, WordClasses)>::new();
+
+    let mut queue = vec![Deinflection {
+        text: text.to_string(),
+        word_class: WordClasses::NONE,
+        inflections: Vec::new(),
+    }];
+
+    while let Some(current) = queue.pop() {
+        for rule in RULES.iter() {
+            // Rules without a source class only apply to the unmodified input
+            let applies = if current.word_class.is_empty() {
+                true
+            }
+            else {
+                current.word_class.intersects(rule.from)
+            };
+            if !applies {
+                continue;
+            }
+
+            let Some(stem) = current.text.strip_suffix(rule.inflected.as_str())
+            else {
+                continue;
+            };
+            if stem.is_empty() && rule.base.is_empty() {
+                continue;
+            }
+
+            let text = format!("{stem}{}", rule.base);
+            if !seen.insert((text.clone(), rule.to)) {
+                continue;
+            }
+
+            let mut inflections = rule.reasons.to_vec();
+            inflections.extend_from_slice(&current.inflections);
+
+            let candidate = Deinflection {
+                text,
+                word_class: rule.to,
+                inflections,
+            };
+            queue.push(candidate.clone());
+            result.push(candidate);
+        }
+    }
+
+    // Intermediate forms aren't words of their own
+    result.retain(|d| {
+        d.word_class.intersects(
+            WordClasses::ICHIDAN
+                | WordClasses::GODAN
+                | WordClasses::KURU
+                | WordClasses::SURU
+                | WordClasses::SURU_NOUN
+                | WordClasses::ADJ_I,
+        )
+    });
+
+    result
+}
+
+struct Rule {
+    inflected: String,
+    base: String,
+    /// Classes the inflected form must belong to. Empty for forms that can't be conjugated any further.
+    from: WordClasses,
+    /// Class of the form produced by this rule
+    to: WordClasses,
+    reasons: &'static [Inflection],
+}
+
+static RULES: LazyLock<Vec<Rule>> = LazyLock::new(build_rules);
+
+fn build_rules() -> Vec<Rule> {
+    use Inflection::*;
+
+    const NONE: WordClasses = WordClasses::NONE;
+    const TE: WordClasses = WordClasses::TE;
+    const PAST: WordClasses = WordClasses::PAST;
+    const MASU: WordClasses = WordClasses::MASU;
+    const ICHIDAN: WordClasses = WordClasses::ICHIDAN;
+    const ADJ_I: WordClasses = WordClasses::ADJ_I;
+
+    let mut rules = Vec::new();
+    let mut rule = |inflected: &str,
+                    base: &str,
+                    from: WordClasses,
+                    to: WordClasses,
+                    reasons: &'static [Inflection]| {
+        rules.push(Rule {
+            inflected: inflected.into(),
+            base: base.into(),
+            from,
+            to,
+            reasons,
+        })
+    };
+
+    // Endings shared by all conjugating forms
+    rule("ました", "ます", PAST, MASU, &[Past]);
+    rule("ません", "ます", NONE, MASU, &[Negative]);
+    rule("ませんでした", "ます", NONE, MASU, &[
+        Negative, Past,
+    ]);
+    rule("ましょう", "ます", NONE, MASU, &[Volitional]);
+    rule("まして", "ます", TE, MASU, &[Te]);
+    rule("ている", "て", ICHIDAN, TE, &[Progressive]);
+    rule("でいる", "で", ICHIDAN, TE, &[Progressive]);
+    rule("てる", "て", ICHIDAN, TE, &[Progressive]);
+    rule("でる", "で", ICHIDAN, TE, &[Progressive]);
+    rule("たら", "た", NONE, PAST, &[Conditional]);
+    rule("だら", "だ", NONE, PAST, &[Conditional]);
+
+    // Ichidan verbs
+    let to = WordClasses::ICHIDAN;
+    rule("ない", "る", ADJ_I, to, &[Negative]);
+    rule("た", "る", PAST, to, &[Past]);
+    rule("て", "る", TE, to, &[Te]);
+    rule("ます", "る", MASU, to, &[Polite]);
+    rule("られる", "る", ICHIDAN, to, &[PotentialOrPassive]);
+    rule("させる", "る", ICHIDAN, to, &[Causative]);
+    rule("ろ", "る", NONE, to, &[Imperative]);
+    rule("よ", "る", NONE, to, &[Imperative]);
+    rule("よう", "る", NONE, to, &[Volitional]);
+    rule("れば", "る", NONE, to, &[Provisional]);
+    rule("たい", "る", ADJ_I, to, &[Desire]);
+
+    // Godan verbs, by dictionary form ending: (u, a, i, e, o, past, te)
+    #[rustfmt::skip]
+    const GODAN: [(&str, &str, &str, &str, &str, &str, &str); 9] = [
+        ("う", "わ", "い", "え", "お", "った", "って"),
+        ("く", "か", "き", "け", "こ", "いた", "いて"),
+        ("ぐ", "が", "ぎ", "げ", "ご", "いだ", "いで"),
+        ("す", "さ", "し", "せ", "そ", "した", "して"),
+        ("つ", "た", "ち", "て", "と", "った", "って"),
+        ("ぬ", "な", "に", "ね", "の", "んだ", "んで"),
+        ("ぶ", "ば", "び", "べ", "ぼ", "んだ", "んで"),
+        ("む", "ま", "み", "め", "も", "んだ", "んで"),
+        ("る", "ら", "り", "れ", "ろ", "った", "って"),
+    ];
+    let to = WordClasses::GODAN;
+    for (u, a, i, e, o, past, te) in GODAN {
+        rule(&format!("{a}ない"), u, ADJ_I, to, &[Negative]);
+        rule(past, u, PAST, to, &[Past]);
+        rule(te, u, TE, to, &[Te]);
+        rule(&format!("{i}ます"), u, MASU, to, &[Polite]);
+        rule(&format!("{a}れる"), u, ICHIDAN, to, &[Passive]);
+        rule(&format!("{e}る"), u, ICHIDAN, to, &[Potential]);
+        rule(&format!("{a}せる"), u, ICHIDAN, to, &[Causative]);
+        rule(e, u, NONE, to, &[Imperative]);
+        rule(&format!("{o}う"), u, NONE, to, &[Volitional]);
+        rule(&format!("{e}ば"), u, NONE, to, &[Provisional]);
+        rule(&format!("{i}たい"), u, ADJ_I, to, &[Desire]);
+    }
+    // 行く is irregular in the past and -te forms
+    for iku in ["行く", "いく", "ゆく"] {
+        let stem = iku.strip_suffix('く').unwrap();
+        rule(&format!("{stem}った"), iku, PAST, to, &[Past]);
+        rule(&format!("{stem}って"), iku, TE, to, &[Te]);
+    }
+
+    // Suru verbs
+    let to = WordClasses::SURU;
+    rule("しない", "する", ADJ_I, to, &[Negative]);
+    rule("した", "する", PAST, to, &[Past]);
+    rule("して", "する", TE, to, &[Te]);
+    rule("します", "する", MASU, to, &[Polite]);
+    rule("される", "する", ICHIDAN, to, &[Passive]);
+    rule("させる", "する", ICHIDAN, to, &[Causative]);
+    rule("できる", "する", ICHIDAN, to, &[Potential]);
+    rule("しろ", "する", NONE, to, &[Imperative]);
+    rule("せよ", "する", NONE, to, &[Imperative]);
+    rule("しよう", "する", NONE, to, &[Volitional]);
+    rule("すれば", "する", NONE, to, &[Provisional]);
+    rule("したい", "する", ADJ_I, to, &[Desire]);
+    // 勉強する -> 勉強
+    rule("する", "", WordClasses::SURU, WordClasses::SURU_NOUN, &[]);
+
+    // Kuru, written in kana or with 来
+    let to = WordClasses::KURU;
+    for (ku, ki, ko) in [("く", "き", "こ"), ("来", "来", "来")] {
+        let base = &format!("{ku}る");
+        rule(&format!("{ko}ない"), base, ADJ_I, to, &[Negative]);
+        rule(&format!("{ki}た"), base, PAST, to, &[Past]);
+        rule(&format!("{ki}て"), base, TE, to, &[Te]);
+        rule(&format!("{ki}ます"), base, MASU, to, &[Polite]);
+        rule(&format!("{ko}られる"), base, ICHIDAN, to, &[
+            PotentialOrPassive,
+        ]);
+        rule(&format!("{ko}させる"), base, ICHIDAN, to, &[Causative]);
+        rule(&format!("{ko}い"), base, NONE, to, &[Imperative]);
+        rule(&format!("{ko}よう"), base, NONE, to, &[Volitional]);
+        rule(&format!("{ku}れば"), base, NONE, to, &[Provisional]);
+        rule(&format!("{ki}たい"), base, ADJ_I, to, &[Desire]);
+    }
+
+    // I-adjectives
+    let to = WordClasses::ADJ_I;
+    rule("くない", "い", ADJ_I, to, &[Negative]);
+    rule("かった", "い", PAST, to, &[Past]);
+    rule("くて", "い", TE, to, &[Te]);
+    rule("ければ", "い", NONE, to, &[Provisional]);
+    rule("く", "い", NONE, to, &[Adverbial]);
+    rule("さ", "い", NONE, to, &[Noun]);
+
+    rules
+}
+
+#[cfg(test)]
+mod test {
+    use super::{deinflect, WordClasses};
+
+    fn chain_for(input: &str, text: &str, class: WordClasses) -> Option<String> {
+        deinflect(input)
+            .into_iter()
+            .find(|d| d.text == text && d.word_class == class)
+            .map(|d| d.chain())
+    }
+
+    #[test]
+    fn test_deinflect() {
+        assert_eq!(
+            chain_for("たべなかった", "たべる", WordClasses::ICHIDAN).as_deref(),
+            Some("negative → past")
+        );
+        assert_eq!(
+            chain_for("食べている", "食べる", WordClasses::ICHIDAN).as_deref(),
+            Some("-te → progressive")
+        );
+        assert_eq!(
+            chain_for("書かせられた", "書く", WordClasses::GODAN).as_deref(),
+            Some("causative → potential or passive → past")
+        );
+        assert_eq!(
+            chain_for("行った", "行く", WordClasses::GODAN).as_deref(),
+            Some("past")
+        );
+        assert_eq!(
+            chain_for("勉強しなかった", "勉強", WordClasses::SURU_NOUN).as_deref(),
+            Some("negative → past")
+        );
+        assert_eq!(
+            chain_for("来なかったら", "来る", WordClasses::KURU).as_deref(),
+            Some("negative → past → -tara")
+        );
+        assert_eq!(
+            chain_for("高くなかった", "高い", WordClasses::ADJ_I).as_deref(),
+            Some("negative → past")
+        );
+
+        // Past tense of a verb can't be read as an adjective
+        assert!(chain_for("食べた", "食べい", WordClasses::ADJ_I).is_none());
+    }
+}
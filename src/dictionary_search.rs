@@ -1,5 +1,10 @@
-use crate::jmdict;
+use egui::ahash::HashMap;
+
+use crate::deinflection::{deinflect, Deinflection, WordClasses};
 use crate::kana::{romaji_to, KanaType};
+use crate::kanjidic2::{self, ReadingType};
+use crate::search_index::SearchIndex;
+use crate::{jmdict, jmnedict};
 
 pub struct SearchWeights {
     pub kanji: u32,
@@ -41,31 +46,19 @@ impl Default for SearchWeights {
 pub fn search(
     text: &str,
     weights: &SearchWeights,
+    languages: &[isolang::Language],
     entries: &[jmdict::Entry],
+    index: &SearchIndex,
     result: &mut Vec<(u32, u32)>,
 ) {
     result.clear();
 
-    if text.trim().len() < 3 {
-        return;
-    }
-
-    let mut groups = Vec::<Vec<String>>::new();
+    let mut groups = Vec::<QueryGroup>::new();
     for piece in text.split_whitespace() {
-        let mut group = vec![piece.to_string()];
-
-        let (failures, hiragana) = romaji_to(KanaType::Hiragana, piece);
-        if failures == 0 {
-            group.push(hiragana);
-            // TODO: Try to deconjugate verbs and adjectives
-        }
-
-        let (failures, katakana) = romaji_to(KanaType::Katakana, piece);
-        if failures == 0 {
-            group.push(katakana);
-        }
-
-        groups.push(group);
+        groups.push(QueryGroup {
+            terms: romaji_variants(piece),
+            deinflections: deinflect_query(piece),
+        });
     }
     if groups.is_empty() {
         return;
@@ -73,24 +66,104 @@ pub fn search(
 
     // Sort groups by length, so that we try to match longer pieces first.
     // This should improve performance, because longer strings are less likely to match, and we can reject more entries in the first pass.
-    groups.sort_by_cached_key(|group| group.iter().map(|w| w.len()).sum::<usize>());
+    groups.sort_by_cached_key(|group| group.terms.iter().map(|w| w.chars().count()).sum::<usize>());
 
     println!("Searching for {:?}", groups);
 
+    // Terms of short queries, for which exact kanji and reading matches are listed first
+    let short_terms: Vec<String> = groups
+        .iter()
+        .flat_map(|group| &group.terms)
+        .filter(|term| is_short_term(term))
+        .cloned()
+        .collect();
+
     let mut iter = groups.into_iter();
 
     let first_group = iter.next().unwrap();
-    for (i, entry) in entries.iter().enumerate() {
-        let score = rate_match(&first_group, entry, weights);
+
+    let mut candidates = Vec::new();
+    for term in &first_group.terms {
+        index.candidates(term, &mut candidates);
+    }
+    for deinflection in &first_group.deinflections {
+        index.candidates(&deinflection.text, &mut candidates);
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    for i in candidates {
+        let score = rate_match(&first_group, &entries[i as usize], weights, languages);
         if score > 0 {
-            result.push((i as u32, score));
+            result.push((i, score));
         }
     }
 
     for remaining_group in iter {
         result.retain_mut(|(entry_idx, score)| {
             let entry = &entries[*entry_idx as usize];
-            *score *= rate_match(&remaining_group, entry, weights);
+            *score *= rate_match(&remaining_group, entry, weights, languages);
+            *score > 0
+        });
+    }
+
+    // Common words come before obscure entries with an equally good match, but never before a better match
+    result.sort_by_cached_key(|(entry_idx, score)| {
+        let entry = &entries[*entry_idx as usize];
+        let exact = short_terms.iter().any(|term| {
+            entry.kanji.iter().any(|k| k.text == *term)
+                || entry.reading.iter().any(|r| r.text == *term)
+        });
+        (
+            std::cmp::Reverse(exact),
+            std::cmp::Reverse(*score),
+            std::cmp::Reverse(entry.is_common()),
+        )
+    });
+}
+
+/// Maximum length in characters (not bytes) of a term that is treated as a short query
+pub const SHORT_TERM_CHARS: usize = 2;
+
+fn is_short_term(term: &str) -> bool { term.chars().count() <= SHORT_TERM_CHARS }
+
+/// Search proper names. Works like [`search`], but without deinflection and with translations in place of glosses.
+pub fn search_names(
+    text: &str,
+    weights: &SearchWeights,
+    names: &jmnedict::NameDictionary,
+    result: &mut Vec<(u32, u32)>,
+) {
+    result.clear();
+
+    let mut groups: Vec<Vec<String>> = text.split_whitespace().map(romaji_variants).collect();
+    if groups.is_empty() {
+        return;
+    }
+    groups.sort_by_cached_key(|group| group.iter().map(|w| w.chars().count()).sum::<usize>());
+
+    let mut iter = groups.into_iter();
+
+    let first_group = iter.next().unwrap();
+
+    let mut candidates = Vec::new();
+    for term in &first_group {
+        names.search_index.candidates(term, &mut candidates);
+    }
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    for i in candidates {
+        let score = rate_name_match(&first_group, &names.entries[i as usize], weights);
+        if score > 0 {
+            result.push((i, score));
+        }
+    }
+
+    for remaining_group in iter {
+        result.retain_mut(|(entry_idx, score)| {
+            let entry = &names.entries[*entry_idx as usize];
+            *score *= rate_name_match(&remaining_group, entry, weights);
             *score > 0
         });
     }
@@ -98,15 +171,231 @@ pub fn search(
     result.sort_unstable_by_key(|(_, score)| std::cmp::Reverse(*score));
 }
 
+/// Order of kanji search results that match equally well.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum KanjiRanking {
+    // Newspaper frequency rank
+    #[default]
+    Frequency,
+    // School grade in which the kanji is taught, then jinmeiyou kanji
+    Grade,
+}
+
+/// Search kanji by on'yomi, kun'yomi, nanori and meaning. Every word of `text` has to match.
+/// Kanji with an exact match (the whole reading or meaning) come first, then those where only
+/// the stem of a kun'yomi or one word of a meaning matches, each sorted according to `ranking`.
+pub fn search_kanji(
+    text: &str,
+    languages: &[isolang::Language],
+    ranking: KanjiRanking,
+    kanji: &HashMap<char, kanjidic2::Character>,
+    result: &mut Vec<char>,
+) {
+    result.clear();
+
+    let groups: Vec<Vec<String>> = text
+        .split_whitespace()
+        .map(|piece| {
+            let mut terms = romaji_variants(piece);
+            terms.push(piece.to_lowercase());
+            terms.iter_mut().for_each(|t| *t = katakana_to_hiragana(t));
+            terms.sort_unstable();
+            terms.dedup();
+            terms
+        })
+        .collect();
+    if groups.is_empty() {
+        return;
+    }
+
+    let mut matches: Vec<(KanjiMatch, &kanjidic2::Character)> = kanji
+        .values()
+        .filter_map(|character| {
+            groups
+                .iter()
+                .map(|terms| rate_kanji_match(terms, character, languages))
+                .try_fold(KanjiMatch::Exact, |worst, m| Some(worst.min(m?)))
+                .map(|m| (m, character))
+        })
+        .collect();
+
+    matches.sort_unstable_by_key(|(kanji_match, character)| {
+        let misc = &character.misc;
+        let frequency = misc.frequency.unwrap_or(u16::MAX);
+        let grade = misc.grade.unwrap_or(u8::MAX);
+        let (first, second) = match ranking {
+            KanjiRanking::Frequency => (frequency, grade as u16),
+            KanjiRanking::Grade => (grade as u16, frequency),
+        };
+        (
+            std::cmp::Reverse(*kanji_match),
+            first,
+            second,
+            misc.stroke_count().unwrap_or(u8::MAX),
+            character.unicode,
+        )
+    });
+    result.extend(matches.into_iter().map(|(_, character)| character.unicode));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum KanjiMatch {
+    // The stem of a kun'yomi, or a word in a meaning
+    Partial,
+    // The kanji itself, a whole reading or a whole meaning
+    Exact,
+}
+
+fn rate_kanji_match(
+    terms: &[String],
+    character: &kanjidic2::Character,
+    languages: &[isolang::Language],
+) -> Option<KanjiMatch> {
+    let mut best = None;
+    for term in terms {
+        let mut chars = term.chars();
+        if chars.next() == Some(character.unicode) && chars.next().is_none() {
+            return Some(KanjiMatch::Exact);
+        }
+
+        for rm in &character.reading_meaning {
+            for nanori in &rm.nanori {
+                if katakana_to_hiragana(nanori) == *term {
+                    return Some(KanjiMatch::Exact);
+                }
+            }
+
+            for rmg in &rm.reading_meaning_groups {
+                for reading in &rmg.readings {
+                    if let Some(kun) = reading.kun() {
+                        if kun.reading() == *term {
+                            return Some(KanjiMatch::Exact);
+                        }
+                        if kun.stem == term {
+                            best = Some(KanjiMatch::Partial);
+                        }
+                    }
+                    else if matches!(reading.typ, ReadingType::Onyomi(_))
+                        && katakana_to_hiragana(reading.value.trim_matches('-')) == *term
+                    {
+                        return Some(KanjiMatch::Exact);
+                    }
+                }
+
+                for meaning in rmg.meanings.iter().filter(|m| languages.contains(&m.lang)) {
+                    let meaning = meaning.text.to_lowercase();
+                    match text_match(term, &meaning) {
+                        Some(MatchKind::Exact) => return Some(KanjiMatch::Exact),
+                        Some(MatchKind::WordExact) => best = Some(KanjiMatch::Partial),
+                        _ => (),
+                    }
+                }
+            }
+        }
+    }
+    best
+}
+
+fn katakana_to_hiragana(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'ァ'..='ヶ' => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// The term itself, plus its hiragana and katakana spelling if it is romaji
+fn romaji_variants(piece: &str) -> Vec<String> {
+    let mut result = vec![piece.to_string()];
+
+    let (failures, hiragana) = romaji_to(KanaType::Hiragana, piece);
+    if failures == 0 {
+        result.push(hiragana);
+    }
+
+    let (failures, katakana) = romaji_to(KanaType::Katakana, piece);
+    if failures == 0 {
+        result.push(katakana);
+    }
+
+    result
+}
+
+/// Candidate dictionary forms for every word of a query, e.g. to show how a result was inflected.
+pub fn deinflect_query(text: &str) -> Vec<Deinflection> {
+    let mut result = Vec::new();
+    for piece in text.split_whitespace() {
+        result.extend(deinflect(piece));
+
+        let (failures, hiragana) = romaji_to(KanaType::Hiragana, piece);
+        if failures == 0 {
+            result.extend(deinflect(&hiragana));
+        }
+    }
+    result
+}
+
+#[derive(Debug)]
+struct QueryGroup {
+    terms: Vec<String>,
+    // Only match kanji and readings exactly, and only if the part of speech fits
+    deinflections: Vec<Deinflection>,
+}
+
 fn rate_match(
-    contains_one_of: &Vec<String>,
+    group: &QueryGroup,
     entry: &jmdict::Entry,
     weights: &SearchWeights,
+    languages: &[isolang::Language],
 ) -> u32 {
     let mut score = 0;
-    for piece in contains_one_of {
+
+    let mut word_class = None;
+    for deinflection in &group.deinflections {
+        let kanji_idx = entry.kanji.iter().position(|k| k.text == deinflection.text);
+        let reading_idx = entry
+            .reading
+            .iter()
+            .position(|r| r.text == deinflection.text);
+        if kanji_idx.is_none() && reading_idx.is_none() {
+            continue;
+        }
+
+        if !word_class
+            .get_or_insert_with(|| WordClasses::of_entry(entry))
+            .intersects(deinflection.word_class)
+        {
+            continue;
+        }
+
+        if let Some(kanji_idx) = kanji_idx {
+            let kanji_score =
+                position_penalty(weights.kanji_position_penalty_pct, kanji_idx as u32)
+                    * weights.exact
+                    * weights.kanji;
+            score = score.max(kanji_score.ceil());
+        }
+        if let Some(reading_idx) = reading_idx {
+            let reading_score =
+                position_penalty(weights.reading_position_penalty_pct, reading_idx as u32)
+                    * weights.exact
+                    * weights.reading;
+            score = score.max(reading_score.ceil());
+        }
+    }
+
+    for piece in &group.terms {
+        // Very short terms are part of too many longer words, so only accept whole words in glosses.
+        // Kanji and readings containing them are still matches, but score lower than those starting with them.
+        let min_gloss_match = match is_short_term(piece) {
+            true => MatchKind::WordExact,
+            false => MatchKind::Contains,
+        };
+
         for (kanji_idx, kanji) in entry.kanji.iter().enumerate() {
-            if let Some(match_score) = text_match(weights, piece, &kanji.text) {
+            if let Some(match_kind) = text_match(piece, &kanji.text) {
+                let match_score = weights.match_score(match_kind);
                 let kanji_score =
                     position_penalty(weights.kanji_position_penalty_pct, kanji_idx as u32)
                         * match_score
@@ -117,7 +406,8 @@ fn rate_match(
         }
 
         for (reading_idx, reading) in entry.reading.iter().enumerate() {
-            if let Some(match_score) = text_match(weights, piece, &reading.text) {
+            if let Some(match_kind) = text_match(piece, &reading.text) {
+                let match_score = weights.match_score(match_kind);
                 let reading_score =
                     position_penalty(weights.reading_position_penalty_pct, reading_idx as u32)
                         * match_score
@@ -129,7 +419,14 @@ fn rate_match(
 
         for (sense_idx, sense) in entry.sense.iter().enumerate() {
             for (gloss_idx, gloss) in sense.glosses.iter().enumerate() {
-                if let Some(match_score) = text_match(weights, piece, &gloss.text) {
+                if !languages.contains(&gloss.lang) {
+                    continue;
+                }
+
+                if let Some(match_kind) =
+                    text_match(piece, &gloss.text).filter(|kind| *kind >= min_gloss_match)
+                {
+                    let match_score = weights.match_score(match_kind);
                     let penalty = position_penalty(
                         weights.sense_position_penalty_pct,
                         sense_idx as u32 + gloss_idx as u32,
@@ -148,6 +445,51 @@ fn rate_match(
     score
 }
 
+fn rate_name_match(terms: &[String], entry: &jmnedict::NameEntry, weights: &SearchWeights) -> u32 {
+    let mut score = 0;
+    for piece in terms {
+        let min_translation_match = match is_short_term(piece) {
+            true => MatchKind::WordExact,
+            false => MatchKind::Contains,
+        };
+
+        for (kanji_idx, kanji) in entry.kanji.iter().enumerate() {
+            if let Some(match_kind) = text_match(piece, kanji) {
+                let kanji_score =
+                    position_penalty(weights.kanji_position_penalty_pct, kanji_idx as u32)
+                        * weights.match_score(match_kind)
+                        * weights.kanji;
+                score = score.max(kanji_score.ceil());
+            }
+        }
+
+        for (reading_idx, reading) in entry.reading.iter().enumerate() {
+            if let Some(match_kind) = text_match(piece, reading) {
+                let reading_score =
+                    position_penalty(weights.reading_position_penalty_pct, reading_idx as u32)
+                        * weights.match_score(match_kind)
+                        * weights.reading;
+                score = score.max(reading_score.ceil());
+            }
+        }
+
+        for (trans_idx, translation) in entry.translations.iter().enumerate() {
+            for detail in &translation.details {
+                if let Some(match_kind) =
+                    text_match(piece, detail).filter(|kind| *kind >= min_translation_match)
+                {
+                    let translation_score =
+                        position_penalty(weights.sense_position_penalty_pct, trans_idx as u32)
+                            * weights.match_score(match_kind)
+                            * weights.sense;
+                    score = score.max(translation_score.ceil());
+                }
+            }
+        }
+    }
+    score
+}
+
 struct Fraction {
     pub numerator:   u32,
     pub denominator: u32,
@@ -178,37 +520,230 @@ fn position_penalty(penalty_pct: u32, position: u32) -> Fraction {
     Fraction::new(1, 1 + position * penalty_pct / 100)
 }
 
-fn text_match(weights: &SearchWeights, term: &str, text: &str) -> Option<u32> {
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MatchKind {
+    Contains,
+    WordStartsWith,
+    StartsWith,
+    WordExact,
+    Exact,
+}
+impl SearchWeights {
+    fn match_score(&self, kind: MatchKind) -> u32 {
+        match kind {
+            MatchKind::Exact => self.exact,
+            MatchKind::WordExact => self.word_exact,
+            MatchKind::StartsWith => self.starts_with,
+            MatchKind::WordStartsWith => self.word_starts_with,
+            MatchKind::Contains => self.contains,
+        }
+    }
+}
+
+/// How well `term` matches `text`, the best of all places where it occurs.
+fn text_match(term: &str, text: &str) -> Option<MatchKind> {
     fn next_char(text: &str, pos: usize) -> Option<char> { text[pos..].chars().next() }
     fn prev_char(text: &str, pos: usize) -> Option<char> { text[..pos].chars().next_back() }
 
-    let pos = text.find(term)?;
+    if text == term {
+        return Some(MatchKind::Exact);
+    }
+
+    text.match_indices(term)
+        .map(|(pos, _)| {
+            let starts_with = pos == 0;
+            let word_starts_with = pos == 0
+                || prev_char(text, pos)
+                    .map(|c| !c.is_alphabetic())
+                    .unwrap_or(false);
+            let word_exact_match = word_starts_with
+                && (pos + term.len() == text.len()
+                    || next_char(text, pos + term.len())
+                        .map(|c| !c.is_alphabetic())
+                        .unwrap_or(false));
+
+            if word_exact_match {
+                MatchKind::WordExact
+            }
+            else if starts_with {
+                MatchKind::StartsWith
+            }
+            else if word_starts_with {
+                MatchKind::WordStartsWith
+            }
+            else {
+                MatchKind::Contains
+            }
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod test {
+    use compact_str::CompactString;
+    use egui::ahash::HashMap;
+
+    use super::{is_short_term, search, search_kanji, search_names, KanjiRanking, SearchWeights};
+    use crate::jmdict::testing::entry;
+    use crate::jmdict::{Entry, EntrySeq, Misc, PartOfSpeech};
+    use crate::jmnedict::{NameDictionary, NameEntry, Translation};
+    use crate::kanjidic2::testing::character;
+    use crate::kanjidic2::ReadingType;
+
+    #[test]
+    fn test_search_kanji() {
+        let kanji = HashMap::from_iter(
+            [
+                character('食')
+                    .frequency(328)
+                    .readings(ReadingType::Onyomi(None), &["ショク"])
+                    .readings(ReadingType::Kunyomi, &["た.べる", "-ぐ.い"])
+                    .meanings(&["eat", "food"]),
+                character('喰')
+                    .frequency(2000)
+                    .readings(ReadingType::Kunyomi, &["く.う", "た.べる"])
+                    .meanings(&["eat", "drink"]),
+                character('職')
+                    .frequency(186)
+                    .readings(ReadingType::Onyomi(None), &["ショク"])
+                    .meanings(&["post", "employment"]),
+                character('多')
+                    .frequency(229)
+                    .readings(ReadingType::Onyomi(None), &["タ"])
+                    .meanings(&["many"]),
+            ]
+            .map(|character| (character.unicode, character)),
+        );
+        let languages = [isolang::Language::Eng];
+        let search = |text| {
+            let mut result = Vec::new();
+            search_kanji(
+                text,
+                &languages,
+                KanjiRanking::Frequency,
+                &kanji,
+                &mut result,
+            );
+            result
+        };
+
+        assert_eq!(search("shoku"), ['職', '食']);
+        assert_eq!(search("しょく"), ['職', '食']);
+        assert_eq!(search("たべる"), ['食', '喰']);
+        assert_eq!(search("ぐい"), ['食']);
+        // Exact on'yomi before kun'yomi stems
+        assert_eq!(search("ta"), ['多', '食', '喰']);
+        assert_eq!(search("Eat"), ['食', '喰']);
+        assert_eq!(search("eat shoku"), ['食']);
+        assert_eq!(search("食"), ['食']);
+        assert!(search("drinking").is_empty());
+    }
 
-    let exact_match = text == term;
-    let starts_with = pos == 0;
-    let word_starts_with = pos == 0
-        || prev_char(text, pos)
-            .map(|c| !c.is_alphabetic())
-            .unwrap_or(false);
-    let word_exact_match = word_starts_with
-        && (pos + term.len() == text.len()
-            || next_char(text, pos + term.len())
-                .map(|c| !c.is_alphabetic())
-                .unwrap_or(false));
+    fn search_words(text: &str, entries: &[Entry]) -> Vec<u32> {
+        let index = crate::search_index::SearchIndex::build(entries);
+        let mut result = Vec::new();
+        search(
+            text,
+            &SearchWeights::default(),
+            &[isolang::Language::Eng],
+            entries,
+            &index,
+            &mut result,
+        );
+        result.into_iter().map(|(idx, _score)| idx).collect()
+    }
 
-    Some(if exact_match {
-        weights.exact
+    #[test]
+    fn test_short_term() {
+        // Counted in chars, 日 is three bytes
+        assert!(is_short_term("日"));
+        assert!(is_short_term("日本"));
+        assert!(is_short_term("ひと"));
+        assert!(!is_short_term("日本語"));
     }
-    else if word_exact_match {
-        weights.word_exact
+
+    #[test]
+    fn test_search_short_queries() {
+        let entries = [
+            entry(1, "日本", "にほん", "Japan").common(),
+            entry(2, "日", "ひ", "day"),
+            entry(3, "毎日", "まいにち", "every day").common(),
+            entry(4, "日本語", "にほんご", "Japanese language").common(),
+            entry(5, "一つ", "ひとつ", "one").common(),
+            entry(6, "人", "ひと", "person"),
+            entry(7, "来て", "きて", "come to me"),
+        ];
+
+        // Exact matches first, even though the longer words are common and would score higher otherwise.
+        // Words containing the term come last.
+        assert_eq!(search_words("日", &entries), [1, 0, 3, 2]);
+        assert_eq!(search_words("日本", &entries)[..2], [0, 3]);
+        assert_eq!(search_words("ひと", &entries)[..2], [5, 4]);
+        assert_eq!(search_words("hito", &entries)[..2], [5, 4]);
+        // Only whole words in glosses, wherever they are
+        assert_eq!(search_words("me", &entries), [6]);
+    }
+
+    #[test]
+    fn test_search_common_words() {
+        let entries = [
+            entry(1, "蛙", "かえる", "frog"),
+            entry(2, "変える", "かえる", "to change").common(),
+            entry(3, "蛙子", "かえるこ", "tadpole").common(),
+        ];
+
+        // Common words first among equal matches, but an obscure exact match still beats a common prefix match
+        assert_eq!(search_words("かえる", &entries), [1, 0, 2]);
     }
-    else if starts_with {
-        weights.starts_with
+
+    #[test]
+    fn test_search_names() {
+        let name = |ent_seq, kanji: &[&str], reading, detail| NameEntry {
+            ent_seq: EntrySeq(ent_seq),
+            kanji: kanji.iter().map(|&k| k.into()).collect(),
+            reading: vec![CompactString::from(reading)],
+            translations: vec![Translation {
+                name_types: vec![Misc::Surname],
+                see_also:   Vec::new(),
+                details:    vec![CompactString::from(detail)],
+            }],
+        };
+        let names = NameDictionary::new(vec![
+            name(1, &["田中"], "たなか", "Tanaka"),
+            name(2, &["中田"], "なかた", "Nakata"),
+            name(3, &[], "ウエノ", "Ueno (Tokyo)"),
+        ]);
+        let search = |text| {
+            let mut result = Vec::new();
+            search_names(text, &SearchWeights::default(), &names, &mut result);
+            result
+                .into_iter()
+                .map(|(idx, _score)| idx)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(search("tanaka"), [0]);
+        assert_eq!(search("たなか"), [0]);
+        // Starting with the term before containing it
+        assert_eq!(search("中"), [1, 0]);
+        // Translations
+        assert_eq!(search("Tokyo"), [2]);
+        assert!(search("Osaka").is_empty());
     }
-    else if word_starts_with {
-        weights.word_starts_with
+
+    #[test]
+    fn test_search_deinflected() {
+        let entries = [
+            entry(1, "食べる", "たべる", "to eat").part_of_speech(PartOfSpeech::V1),
+            entry(2, "帰る", "かえる", "to return").part_of_speech(PartOfSpeech::V5r),
+            entry(3, "蛙", "かえる", "frog").part_of_speech(PartOfSpeech::N),
+            // No part of speech, so it is never a conjugation
+            entry(4, "書く", "かく", "to write"),
+        ];
+
+        assert_eq!(search_words("食べなかった", &entries), [0]);
+        // Only the verb conjugates
+        assert_eq!(search_words("かえった", &entries), [1]);
+        assert!(search_words("書かない", &entries).is_empty());
     }
-    else {
-        weights.contains
-    })
 }
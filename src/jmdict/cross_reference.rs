@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use compact_str::CompactString;
+
+use super::{Entry, EntrySeq, SenseTag};
+
+// xref, ant
+// Written as "keb・reb・sense number", where the reading and sense number are optional
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct CrossReference {
+    // keb, or reb for kana-only words
+    pub text:    CompactString,
+    pub reading: Option<CompactString>,
+    // Index into Entry::sense, zero-based unlike in the source text
+    pub sense:   Option<u32>,
+    // Resolved by resolve_cross_references
+    pub target:  Option<EntrySeq>,
+}
+impl FromStr for CrossReference {
+    type Err = std::convert::Infallible;
+
+    /// ```
+    /// use jdict2::jmdict::CrossReference;
+    ///
+    /// let xref: CrossReference = "食べる・たべる・2".parse().unwrap();
+    /// assert_eq!(xref.text, "食べる");
+    /// assert_eq!(xref.reading.as_deref(), Some("たべる"));
+    /// assert_eq!(xref.sense, Some(1));
+    ///
+    /// let xref: CrossReference = "ごちそうさま・1".parse().unwrap();
+    /// assert_eq!(xref.text, "ごちそうさま");
+    /// assert_eq!(xref.reading, None);
+    /// assert_eq!(xref.sense, Some(0));
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('・').collect();
+
+        let mut sense = None;
+        if parts.len() > 1 {
+            if let Ok(number) = parts.last().unwrap().parse::<u32>() {
+                sense = number.checked_sub(1);
+                parts.pop();
+            }
+        }
+
+        let (text, reading) = match parts.as_slice() {
+            [text] => (CompactString::from(*text), None),
+            [text, reading] => (
+                CompactString::from(*text),
+                Some(CompactString::from(*reading)),
+            ),
+            // The word itself contains a ・
+            _ => (CompactString::from(parts.join("・")), None),
+        };
+
+        Ok(CrossReference {
+            text,
+            reading,
+            sense,
+            target: None,
+        })
+    }
+}
+impl Display for CrossReference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.text)?;
+        if let Some(reading) = &self.reading {
+            write!(f, "・{reading}")?;
+        }
+        if let Some(sense) = self.sense {
+            write!(f, "・{}", sense + 1)?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the entry each `<xref>` and `<ant>` refers to, and store its sequence number in [`CrossReference::target`].
+/// References that don't match any entry, or whose sense number doesn't exist, stay unresolved.
+pub fn resolve_cross_references(entries: &mut [Entry]) {
+    let mut by_text = HashMap::<CompactString, Vec<usize>>::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        let texts = entry
+            .kanji
+            .iter()
+            .map(|k| &k.text)
+            .chain(entry.reading.iter().map(|r| &r.text));
+        for text in texts {
+            let indices = by_text.entry(text.clone()).or_default();
+            if indices.last() != Some(&idx) {
+                indices.push(idx);
+            }
+        }
+    }
+
+    let mut resolved = Vec::new();
+    for (idx, entry) in entries.iter().enumerate() {
+        for (sense_idx, sense) in entry.sense.iter().enumerate() {
+            for (tag_idx, tag) in sense.tags.iter().enumerate() {
+                let (SenseTag::SeeAlso(xref) | SenseTag::Antonym(xref)) = tag
+                else {
+                    continue;
+                };
+
+                let target = by_text
+                    .get(&xref.text)
+                    .into_iter()
+                    .flatten()
+                    .map(|&target_idx| &entries[target_idx])
+                    .filter(|target| !std::ptr::eq(*target, entry))
+                    .find(|target| {
+                        let reading_matches = match &xref.reading {
+                            Some(reading) => target.reading.iter().any(|r| r.text == *reading),
+                            None => true,
+                        };
+                        let sense_exists = match xref.sense {
+                            Some(sense) => (sense as usize) < target.sense.len(),
+                            None => true,
+                        };
+                        reading_matches && sense_exists
+                    });
+
+                if let Some(target) = target {
+                    resolved.push((idx, sense_idx, tag_idx, target.ent_seq));
+                }
+            }
+        }
+    }
+
+    for (idx, sense_idx, tag_idx, seq) in resolved {
+        if let SenseTag::SeeAlso(xref) | SenseTag::Antonym(xref) =
+            &mut entries[idx].sense[sense_idx].tags[tag_idx]
+        {
+            xref.target = Some(seq);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::resolve_cross_references;
+    use crate::jmdict::{EntrySeq, SenseTag};
+    use crate::parse_error::{ParseContext, ParseMode};
+
+    #[test]
+    fn test_resolve_cross_references() {
+        let xml = r#"<JMdict>
+<entry>
+<ent_seq>1</ent_seq>
+<k_ele><keb>上手</keb></k_ele>
+<r_ele><reb>じょうず</reb></r_ele>
+<sense><xref>上手</xref><gloss>skillful</gloss></sense>
+<sense><gloss>flattery</gloss></sense>
+</entry>
+<entry>
+<ent_seq>2</ent_seq>
+<k_ele><keb>上手</keb></k_ele>
+<r_ele><reb>うわて</reb></r_ele>
+<sense><gloss>upper part</gloss></sense>
+<sense><gloss>upstream</gloss></sense>
+<sense><gloss>superior</gloss></sense>
+</entry>
+<entry>
+<ent_seq>3</ent_seq>
+<k_ele><keb>上手</keb></k_ele>
+<r_ele><reb>かみて</reb></r_ele>
+<sense><gloss>stage left</gloss></sense>
+</entry>
+<entry>
+<ent_seq>4</ent_seq>
+<r_ele><reb>ごちそうさま</reb></r_ele>
+<sense>
+<xref>上手・うわて・3</xref>
+<xref>上手・かみて</xref>
+<xref>上手・かみて・2</xref>
+<xref>上手・2</xref>
+<xref>じょうず</xref>
+<ant>存在しない</ant>
+<gloss>thank you for the meal</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>5</ent_seq>
+<k_ele><keb>頂きます</keb></k_ele>
+<r_ele><reb>いただきます</reb></r_ele>
+<sense><xref>ごちそうさま</xref><gloss>thank you for the food</gloss></sense>
+</entry>
+</JMdict>"#;
+        let document = roxmltree::Document::parse(xml).unwrap();
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let mut entries =
+            crate::jmdict::parsing::parse_jmdict(document.root_element(), &mut ctx).unwrap();
+        resolve_cross_references(&mut entries);
+
+        let targets = |idx: usize, sense: usize| -> Vec<Option<EntrySeq>> {
+            entries[idx].sense[sense]
+                .tags
+                .iter()
+                .filter_map(|tag| match tag {
+                    SenseTag::SeeAlso(xref) | SenseTag::Antonym(xref) => Some(xref.target),
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // Doesn't resolve to itself, but to the other entry with the same kanji
+        assert_eq!(targets(0, 0), [Some(EntrySeq(2))]);
+        assert_eq!(targets(3, 0), [
+            // Disambiguated by reading and sense
+            Some(EntrySeq(2)),
+            Some(EntrySeq(3)),
+            // Sense 2 of かみて doesn't exist
+            None,
+            // Without a reading, the first entry with a second sense
+            Some(EntrySeq(1)),
+            // Only a reading
+            Some(EntrySeq(1)),
+            // No such word
+            None,
+        ]);
+        // Kana-only target
+        assert_eq!(targets(4, 0), [Some(EntrySeq(4))]);
+
+        // Targets and the sequence numbers they refer to survive saving the database
+        let blob = postcard::to_stdvec(&entries).unwrap();
+        let loaded: Vec<crate::jmdict::Entry> = postcard::from_bytes(&blob).unwrap();
+        assert_eq!(loaded[1].ent_seq, EntrySeq(2));
+        assert!(matches!(
+            &loaded[0].sense[0].tags[..],
+            [SenseTag::SeeAlso(xref)] if xref.target == Some(EntrySeq(2))
+        ));
+    }
+}
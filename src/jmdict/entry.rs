@@ -4,14 +4,30 @@ use super::{Kanji, Reading, Sense};
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Entry {
-    #[serde(skip)]
     pub ent_seq: EntrySeq,
     pub kanji:   Vec<Kanji>,
     pub reading: Vec<Reading>,
     pub sense:   Vec<Sense>,
 }
+impl Entry {
+    /// Whether any kanji or reading element is marked as a common word.
+    pub fn is_common(&self) -> bool {
+        self.kanji
+            .iter()
+            .flat_map(|k| &k.prio)
+            .any(|p| p.is_common())
+            || self
+                .reading
+                .iter()
+                .flat_map(|r| &r.prio)
+                .any(|p| p.is_common())
+    }
+}
 
-#[derive(serde::Serialize, serde::Deserialize)]
+// ent_seq
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
 pub struct EntrySeq(pub u32);
 impl EntrySeq {
     pub const INVALID: EntrySeq = EntrySeq(u32::MAX);
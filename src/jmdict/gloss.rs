@@ -3,7 +3,6 @@ pub struct Gloss {
     pub text: compact_str::CompactString,
 
     // attribute xml:lang
-    #[serde(skip)]
     pub lang: isolang::Language,
     // pri
     pub highlight: bool,
@@ -17,7 +16,18 @@ pub struct Gloss {
 
 pub enum GlossTags {}
 
-pub struct Priority {}
+/// Languages the multilingual JMdict provides glosses in, with their English names.
+pub const GLOSS_LANGUAGES: [(isolang::Language, &str); 9] = [
+    (isolang::Language::Eng, "English"),
+    (isolang::Language::Nld, "Dutch"),
+    (isolang::Language::Fra, "French"),
+    (isolang::Language::Deu, "German"),
+    (isolang::Language::Hun, "Hungarian"),
+    (isolang::Language::Rus, "Russian"),
+    (isolang::Language::Slv, "Slovenian"),
+    (isolang::Language::Spa, "Spanish"),
+    (isolang::Language::Swe, "Swedish"),
+];
 
 // #[derive(Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
 // pub struct Lang([u8; 3]);
@@ -1,3 +1,5 @@
+use super::Priority;
+
 // k_ele
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Kanji {
@@ -6,7 +8,6 @@ pub struct Kanji {
     // ke_inf
     pub info: Vec<KanjiInfo>,
     // ke_pri
-    #[serde(skip)]
     pub prio: Vec<KanjiPrio>,
 }
 
@@ -39,5 +40,4 @@ pub enum KanjiInfo {
 }
 
 // ke_pri
-#[derive(serde::Serialize, serde::Deserialize)]
-pub enum KanjiPrio {}
+pub type KanjiPrio = Priority;
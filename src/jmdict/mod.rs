@@ -1,12 +1,17 @@
+pub mod cross_reference;
 pub mod entry;
 pub mod gloss;
 pub mod kanji;
 pub mod parsing;
+pub mod priority;
 pub mod reading;
 pub mod sense;
+#[cfg(test)] pub(crate) mod testing;
 
+pub use cross_reference::*;
 pub use entry::*;
 pub use gloss::*;
 pub use kanji::*;
+pub use priority::*;
 pub use reading::*;
 pub use sense::*;
@@ -1,59 +1,133 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+
 use compact_str::CompactString;
 use roxmltree::NodeType;
 
 use super::entry::EntrySeq;
 use super::{Entry, Gloss, Reading, SourceLanguage};
-use crate::jmdict::{CrossReference, GlossType, Kanji, Sense, SenseTag};
+use crate::jmdict::{GlossType, Kanji, Sense, SenseTag};
+use crate::parse_error::{ParseContext, ParseError, ParseErrorKind};
+use crate::xml_stream::{entity_declarations, XmlStream};
+
+pub fn parse_jmdict(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Vec<Entry>, ParseError> {
+    if node.tag_name().name() != "JMdict" {
+        return Err(ctx.error(
+            node,
+            ParseErrorKind::UnexpectedElement(node.tag_name().name().into()),
+        ));
+    }
 
-pub fn parse_jmdict(node: roxmltree::Node) -> Vec<Entry> {
-    assert_eq!(node.tag_name().name(), "JMdict");
+    let entities = EntityNames::from_dtd(node.document().input_text());
 
     let mut result = Vec::new();
 
     for child in node.children() {
         match (child.node_type(), child.tag_name().name()) {
-            (NodeType::Element, "entry") => result.push(parse_entry(child)),
-            (NodeType::Text, _) => {}
-            (typ, name) => panic!("Unexpected child in <JMdict>: {typ:?} {name}"),
+            (NodeType::Element, "entry") => result.push(parse_entry(child, &entities, ctx)?),
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
         }
     }
 
-    result
+    Ok(result)
+}
+
+/// Like [`parse_jmdict`], but reads the document one `<entry>` at a time instead of loading it as a whole.
+pub fn parse_jmdict_stream(
+    reader: impl BufRead,
+    ctx: &mut ParseContext,
+) -> Result<Vec<Entry>, ParseError> {
+    let mut stream = XmlStream::new(reader)?;
+    stream.expect_root("JMdict")?;
+
+    let prolog = stream.prolog().to_owned();
+    let entities = EntityNames::from_dtd(&prolog);
+
+    let mut result = Vec::new();
+
+    stream.for_each_element(ctx, |node, ctx| {
+        match node.tag_name().name() {
+            "entry" => result.push(parse_entry(node, &entities, ctx)?),
+            _ => ctx.unexpected(node)?,
+        }
+        Ok(())
+    })?;
+
+    Ok(result)
 }
 
-pub fn parse_entry(node: roxmltree::Node) -> Entry {
+/// Tags like `<pos>&v1;</pos>` are DTD entities, which roxmltree expands to their description ("Ichidan verb").
+/// This maps the descriptions back to the entity names, which are much more stable between JMdict releases.
+pub struct EntityNames<'input>(HashMap<&'input str, &'input str>);
+impl<'input> EntityNames<'input> {
+    /// Collect all `<!ENTITY name "description">` declarations from the document's internal DTD.
+    pub fn from_dtd(input: &'input str) -> Self {
+        EntityNames(
+            entity_declarations(input)
+                .map(|(name, value)| (value, name))
+                .collect(),
+        )
+    }
+
+    /// Parse the text of an element that contains a single entity, preferring the entity name over the description.
+    pub(crate) fn parse<T: std::str::FromStr>(&self, text: &str) -> Result<T, T::Err> {
+        match self.0.get(text) {
+            Some(name) => name.parse().or_else(|_| text.parse()),
+            None => text.parse(),
+        }
+    }
+}
+
+pub fn parse_entry(
+    node: roxmltree::Node,
+    entities: &EntityNames,
+    ctx: &mut ParseContext,
+) -> Result<Entry, ParseError> {
     assert_eq!(node.tag_name().name(), "entry");
 
     let mut result = Entry {
-        ent_seq: EntrySeq(0),
+        ent_seq: EntrySeq::INVALID,
         kanji:   Vec::new(),
         reading: Vec::new(),
         sense:   Vec::new(),
     };
 
+    let mut has_ent_seq = false;
     for child in node.children() {
         match (child.node_type(), child.tag_name().name()) {
             (NodeType::Element, "ent_seq") => {
-                result.ent_seq = child.text().unwrap().parse().unwrap();
+                has_ent_seq = true;
+                if let Some(ent_seq) = ctx.parse_text(child)? {
+                    result.ent_seq = ent_seq;
+                }
             }
             (NodeType::Element, "r_ele") => {
-                result.reading.push(parse_r_ele(child));
+                result.reading.push(parse_r_ele(child, ctx)?);
             }
             (NodeType::Element, "k_ele") => {
-                result.kanji.push(parse_k_ele(child));
+                result.kanji.push(parse_k_ele(child, ctx)?);
             }
             (NodeType::Element, "sense") => {
-                result.sense.push(parse_sense(child));
+                result.sense.push(parse_sense(child, entities, ctx)?);
             }
-            (NodeType::Text, _) => {}
-            (typ, name) => panic!("Unexpected child in <entry>: {typ:?} {name}"),
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
         }
     }
+    // Without it, the entry keeps EntrySeq::INVALID and can't be the target of a cross-reference
+    if !has_ent_seq {
+        let error = ctx.error(node, ParseErrorKind::MissingChild("ent_seq"));
+        ctx.recover(error)?;
+    }
 
-    result
+    Ok(result)
 }
 
-fn parse_r_ele(node: roxmltree::Node) -> Reading {
+fn parse_r_ele(node: roxmltree::Node, ctx: &mut ParseContext) -> Result<Reading, ParseError> {
     assert_eq!(node.tag_name().name(), "r_ele");
 
     let mut result = Reading {
@@ -67,29 +141,33 @@ fn parse_r_ele(node: roxmltree::Node) -> Reading {
     for child in node.children() {
         match (child.node_type(), child.tag_name().name()) {
             (NodeType::Element, "reb") => {
-                result.text = child.text().unwrap().into();
+                result.text = ctx.text(child)?.unwrap_or_default().into();
             }
             (NodeType::Element, "re_nokanji") => {
                 result.no_kanji = true;
             }
             (NodeType::Element, "re_restr") => {
-                result.restrict_to_kanji.push(child.text().unwrap().into());
+                if let Some(text) = ctx.text(child)? {
+                    result.restrict_to_kanji.push(text.into());
+                }
             }
             (NodeType::Element, "re_inf") => {
                 // TODO
             }
             (NodeType::Element, "re_pri") => {
-                // TODO
+                if let Some(prio) = ctx.parse_text(child)? {
+                    result.prio.push(prio);
+                }
             }
-            (NodeType::Text, _) => {}
-            (typ, name) => panic!("Unexpected child in <r_ele>: {typ:?} {name}"),
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
         }
     }
 
-    result
+    Ok(result)
 }
 
-fn parse_k_ele(node: roxmltree::Node) -> Kanji {
+fn parse_k_ele(node: roxmltree::Node, ctx: &mut ParseContext) -> Result<Kanji, ParseError> {
     assert_eq!(node.tag_name().name(), "k_ele");
 
     let mut result = Kanji {
@@ -101,23 +179,29 @@ fn parse_k_ele(node: roxmltree::Node) -> Kanji {
     for child in node.children() {
         match (child.node_type(), child.tag_name().name()) {
             (NodeType::Element, "keb") => {
-                result.text = child.text().unwrap().into();
+                result.text = ctx.text(child)?.unwrap_or_default().into();
             }
             (NodeType::Element, "ke_pri") => {
-                // TODO
+                if let Some(prio) = ctx.parse_text(child)? {
+                    result.prio.push(prio);
+                }
             }
             (NodeType::Element, "ke_inf") => {
                 // TODO
             }
-            (NodeType::Text, _) => {}
-            (typ, name) => panic!("Unexpected child in <k_ele>: {typ:?} {name}"),
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
         }
     }
 
-    result
+    Ok(result)
 }
 
-fn parse_sense(node: roxmltree::Node) -> Sense {
+fn parse_sense(
+    node: roxmltree::Node,
+    entities: &EntityNames,
+    ctx: &mut ParseContext,
+) -> Result<Sense, ParseError> {
     assert_eq!(node.tag_name().name(), "sense");
 
     let mut result = Sense {
@@ -126,70 +210,53 @@ fn parse_sense(node: roxmltree::Node) -> Sense {
     };
 
     for child in node.children() {
-        match (child.node_type(), child.tag_name().name()) {
-            (NodeType::Element, "stagk") => {
-                result
-                    .tags
-                    .push(SenseTag::OnlyForKanji(child.text().unwrap().into()));
-            }
-            (NodeType::Element, "stagr") => {
-                result
-                    .tags
-                    .push(SenseTag::OnlyForReading(child.text().unwrap().into()));
-            }
-            (NodeType::Element, "pos") => {
-                // println!("pos: {:?}", child.text().unwrap());
-                // result.tags.push(SenseTag::PartOfSpeech(
-                //     child.text().unwrap().parse().unwrap(),
-                // ));
-            }
-            (NodeType::Element, "xref") => {
-                result.tags.push(SenseTag::SeeAlso(CrossReference(
-                    child.text().unwrap().into(),
-                )));
-            }
-            (NodeType::Element, "ant") => {
-                result.tags.push(SenseTag::Antonym(CrossReference(
-                    child.text().unwrap().into(),
-                )));
-            }
-            (NodeType::Element, "field") => {
-                result
-                    .tags
-                    .push(SenseTag::Field(child.text().unwrap().parse().unwrap()));
-            }
-            (NodeType::Element, "misc") => {
-                result
-                    .tags
-                    .push(SenseTag::Misc(child.text().unwrap().parse().unwrap()));
-            }
-            (NodeType::Element, "s_inf") => {
-                result
-                    .tags
-                    .push(SenseTag::Info(child.text().unwrap().to_string()));
-            }
+        let tag = match (child.node_type(), child.tag_name().name()) {
+            (NodeType::Element, "stagk") => ctx
+                .text(child)?
+                .map(|text| SenseTag::OnlyForKanji(text.into())),
+            (NodeType::Element, "stagr") => ctx
+                .text(child)?
+                .map(|text| SenseTag::OnlyForReading(text.into())),
+            (NodeType::Element, "pos") => ctx
+                .parse_text_with(child, |text| entities.parse(text))?
+                .map(SenseTag::PartOfSpeech),
+            (NodeType::Element, "xref") => ctx.parse_text(child)?.map(SenseTag::SeeAlso),
+            (NodeType::Element, "ant") => ctx.parse_text(child)?.map(SenseTag::Antonym),
+            (NodeType::Element, "field") => ctx
+                .parse_text_with(child, |text| entities.parse(text))?
+                .map(SenseTag::Field),
+            (NodeType::Element, "misc") => ctx
+                .parse_text_with(child, |text| entities.parse(text))?
+                .map(SenseTag::Misc),
+            (NodeType::Element, "s_inf") => ctx
+                .text(child)?
+                .map(|text| SenseTag::Info(text.to_string())),
             (NodeType::Element, "lsource") => {
-                result
-                    .tags
-                    .push(SenseTag::SourceLanguage(parse_lsource(child)));
-            }
-            (NodeType::Element, "dial") => {
-                result
-                    .tags
-                    .push(SenseTag::Dialect(child.text().unwrap().parse().unwrap()));
+                Some(SenseTag::SourceLanguage(parse_lsource(child, ctx)?))
             }
+            (NodeType::Element, "dial") => ctx
+                .parse_text_with(child, |text| entities.parse(text))?
+                .map(SenseTag::Dialect),
             (NodeType::Element, "gloss") => {
-                result.glosses.push(parse_gloss(child));
+                result.glosses.push(parse_gloss(child, ctx)?);
+                None
             }
-            (NodeType::Text, _) => {}
-            (typ, name) => panic!("Unexpected child in <sense>: {typ:?} {name}"),
-        }
+            (NodeType::Text | NodeType::Comment, _) => None,
+            _ => {
+                ctx.unexpected(child)?;
+                None
+            }
+        };
+        result.tags.extend(tag);
     }
 
-    result
+    Ok(result)
 }
 
-fn parse_lsource(node: roxmltree::Node) -> SourceLanguage {
+fn parse_lsource(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<SourceLanguage, ParseError> {
     assert_eq!(node.tag_name().name(), "lsource");
 
     let mut result = SourceLanguage {
@@ -200,30 +267,7 @@ fn parse_lsource(node: roxmltree::Node) -> SourceLanguage {
     for attrib in node.attributes() {
         match attrib.name() {
             "lang" => {
-                result.lang = match attrib.value() {
-                    "tib" => isolang::Language::Bod,
-                    "cze" => isolang::Language::Ces,
-                    "wel" => isolang::Language::Cym,
-                    "ger" => isolang::Language::Deu,
-                    "gre" => isolang::Language::Ell,
-                    "baq" => isolang::Language::Eus,
-                    "per" => isolang::Language::Fas,
-                    "fre" => isolang::Language::Fra,
-                    "arm" => isolang::Language::Hye,
-                    "ice" => isolang::Language::Isl,
-                    "geo" => isolang::Language::Kat,
-                    "mac" => isolang::Language::Mkd,
-                    "mao" => isolang::Language::Mri,
-                    "may" => isolang::Language::Msa,
-                    "bur" => isolang::Language::Mya,
-                    "dut" => isolang::Language::Nld,
-                    "rum" => isolang::Language::Ron,
-                    "slo" => isolang::Language::Slk,
-                    "alb" => isolang::Language::Sqi,
-                    "chi" => isolang::Language::Zho,
-                    "mol" => isolang::Language::Bul,
-                    lang => lang.parse().unwrap_or(isolang::Language::Und),
-                };
+                result.lang = parse_language(attrib.value());
             }
             "ls_type" => {
                 // TODO
@@ -231,22 +275,44 @@ fn parse_lsource(node: roxmltree::Node) -> SourceLanguage {
             "ls_wasei" => {
                 // TODO
             }
-            _ => {
-                panic!(
-                    "Unexpected attribute in <lsource>: {} ({})",
-                    attrib.name(),
-                    attrib.namespace().unwrap_or(""),
-                )
-            }
+            _ => ctx.unexpected_attribute(node, &attrib)?,
         }
     }
 
     result.text = node.text().map(|s| s.into()).unwrap_or_default();
 
-    result
+    Ok(result)
 }
 
-fn parse_gloss(node: roxmltree::Node) -> Gloss {
+// JMdict uses ISO 639-2/B codes, which partly differ from ISO 639-3
+fn parse_language(code: &str) -> isolang::Language {
+    match code {
+        "tib" => isolang::Language::Bod,
+        "cze" => isolang::Language::Ces,
+        "wel" => isolang::Language::Cym,
+        "ger" => isolang::Language::Deu,
+        "gre" => isolang::Language::Ell,
+        "baq" => isolang::Language::Eus,
+        "per" => isolang::Language::Fas,
+        "fre" => isolang::Language::Fra,
+        "arm" => isolang::Language::Hye,
+        "ice" => isolang::Language::Isl,
+        "geo" => isolang::Language::Kat,
+        "mac" => isolang::Language::Mkd,
+        "mao" => isolang::Language::Mri,
+        "may" => isolang::Language::Msa,
+        "bur" => isolang::Language::Mya,
+        "dut" => isolang::Language::Nld,
+        "rum" => isolang::Language::Ron,
+        "slo" => isolang::Language::Slk,
+        "alb" => isolang::Language::Sqi,
+        "chi" => isolang::Language::Zho,
+        "mol" => isolang::Language::Bul,
+        lang => lang.parse().unwrap_or(isolang::Language::Und),
+    }
+}
+
+fn parse_gloss(node: roxmltree::Node, ctx: &mut ParseContext) -> Result<Gloss, ParseError> {
     assert_eq!(node.tag_name().name(), "gloss");
 
     let mut result = Gloss {
@@ -259,30 +325,155 @@ fn parse_gloss(node: roxmltree::Node) -> Gloss {
 
     for attrib in node.attributes() {
         match attrib.name() {
-            "xml:lang" => {
-                result.lang = attrib.value().parse().unwrap();
+            // xml:lang
+            "lang" => {
+                result.lang = parse_language(attrib.value());
             }
             "g_type" => {
-                result.typ = attrib.value().parse().unwrap();
+                if let Some(typ) = ctx.parse_value(node, attrib.value(), str::parse)? {
+                    result.typ = typ;
+                }
             }
             "g_gend" => {
                 result.gender = Some(attrib.value().into());
             }
-            _ => panic!("Unexpected attribute in <gloss>: {}", attrib.name()),
+            _ => ctx.unexpected_attribute(node, &attrib)?,
         }
     }
 
-    result.text = node.text().unwrap().into();
+    result.text = ctx.text(node)?.unwrap_or_default().into();
 
     for child in node.children() {
         match (child.node_type(), child.tag_name().name()) {
             (NodeType::Element, "pri") => {
                 result.highlight = true;
             }
-            (NodeType::Text, _) => {}
-            (typ, name) => panic!("Unexpected child in <gloss>: {typ:?} {name}"),
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
         }
     }
 
-    result
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::jmdict::{EntrySeq, PartOfSpeech, SenseTag};
+    use crate::parse_error::{ParseContext, ParseErrorKind, ParseMode};
+
+    #[test]
+    fn test_parse_entry() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ENTITY v1 "Ichidan verb">
+<!ENTITY vt "a description that changed upstream">
+]>
+<JMdict>
+<entry>
+<ent_seq>1358280</ent_seq>
+<k_ele><keb>食べる</keb><ke_pri>ichi1</ke_pri></k_ele>
+<r_ele><reb>たべる</reb></r_ele>
+<sense><pos>&v1;</pos><pos>&vt;</pos><gloss>to eat</gloss></sense>
+<sense><gloss xml:lang="ger">essen</gloss></sense>
+</entry>
+</JMdict>"#;
+        let document = roxmltree::Document::parse_with_options(xml, roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let entries = super::parse_jmdict(document.root_element(), &mut ctx).unwrap();
+        let pos: Vec<_> = entries[0].sense[0]
+            .tags
+            .iter()
+            .filter_map(|tag| match tag {
+                SenseTag::PartOfSpeech(pos) => Some(*pos),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(pos, [PartOfSpeech::V1, PartOfSpeech::Vt]);
+        assert!(entries[0].is_common());
+        assert_eq!(entries[0].sense[0].glosses[0].lang, isolang::Language::Eng);
+        assert_eq!(entries[0].sense[1].glosses[0].lang, isolang::Language::Deu);
+    }
+
+    #[test]
+    fn test_lenient_parsing() {
+        let xml = r#"<JMdict>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele><reb>ゆ</reb><re_unknown/></r_ele>
+<sense><pos>not a part of speech</pos><gloss>hot water</gloss></sense>
+</entry>
+<entry>
+<r_ele><reb>みず</reb></r_ele>
+<sense><gloss>water</gloss></sense>
+</entry>
+</JMdict>"#;
+        let document = roxmltree::Document::parse(xml).unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let Err(error) = super::parse_jmdict(document.root_element(), &mut ctx)
+        else {
+            panic!("Expected an error in strict mode");
+        };
+        assert_eq!(error.path, "JMdict/entry/r_ele/re_unknown");
+        assert_eq!((error.pos.row, error.pos.col), (4, 20));
+
+        let mut ctx = ParseContext::new(ParseMode::Lenient);
+        let entries = super::parse_jmdict(document.root_element(), &mut ctx).unwrap();
+        assert_eq!(entries[0].reading[0].text, "ゆ");
+        assert_eq!(entries[0].sense[0].glosses[0].text, "hot water");
+        assert!(entries[0].sense[0].tags.is_empty());
+        assert_eq!(ctx.skipped.len(), 3);
+        assert_eq!(ctx.skipped[1].path, "JMdict/entry/sense/pos");
+        // Kept, but never found by sequence number
+        assert_eq!(entries[1].ent_seq, EntrySeq::INVALID);
+        assert!(matches!(
+            ctx.skipped[2].kind,
+            ParseErrorKind::MissingChild("ent_seq")
+        ));
+    }
+
+    #[test]
+    fn test_parse_stream() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ELEMENT JMdict (entry*)>
+<!-- <entry> is the main element -->
+<!ENTITY v1 "Ichidan verb">
+<!ENTITY uk "word usually written using kana alone">
+]>
+<JMdict>
+<!-- JMdict created: 2024-01-01 -->
+<entry>
+<ent_seq>1358280</ent_seq>
+<k_ele><keb>食べる</keb><ke_pri>ichi1</ke_pri></k_ele>
+<r_ele><reb>たべる</reb></r_ele>
+<sense><pos>&v1;</pos><xref>喰う・くう・1</xref><gloss>to eat</gloss><gloss g_type="fig">to live on (e.g. a salary)</gloss></sense>
+</entry>
+<entry>
+<ent_seq>1000000</ent_seq>
+<r_ele><reb>ゆ</reb></r_ele>
+<sense><misc>&uk;</misc><gloss>hot water &amp; steam</gloss></sense>
+</entry>
+</JMdict>"#;
+        let document = roxmltree::Document::parse_with_options(xml, roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let entries = super::parse_jmdict(document.root_element(), &mut ctx).unwrap();
+        let streamed = super::parse_jmdict_stream(xml.as_bytes(), &mut ctx).unwrap();
+
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(
+            postcard::to_allocvec(&entries).unwrap(),
+            postcard::to_allocvec(&streamed).unwrap()
+        );
+    }
 }
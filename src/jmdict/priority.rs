@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+// ke_pri, re_pri
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Priority {
+    /// Appears in the "wordfreq" file compiled from the Mainichi Shimbun (news1: top 12,000, news2: the rest)
+    News(u8),
+    /// Appears in the "Ichimango goi bunruishuu" (ichi2: demoted due to low frequency in other sources)
+    Ichi(u8),
+    /// Detected as common but not included in the other lists
+    Spec(u8),
+    /// Common loanword, based on the wordfreq file
+    Gai(u8),
+    /// Frequency bucket of 500 words in the wordfreq file (nf01: most common)
+    Nf(u8),
+}
+impl Priority {
+    /// Whether this code marks a "common word", as defined by the JMdict documentation (news1, ichi1, spec1, spec2, gai1).
+    /// ```
+    /// use jdict2::jmdict::Priority;
+    ///
+    /// assert!("ichi1".parse::<Priority>().unwrap().is_common());
+    /// assert!("spec2".parse::<Priority>().unwrap().is_common());
+    /// assert!(!"news2".parse::<Priority>().unwrap().is_common());
+    /// assert!(!"nf01".parse::<Priority>().unwrap().is_common());
+    /// ```
+    pub fn is_common(&self) -> bool {
+        matches!(
+            self,
+            Priority::News(1) | Priority::Ichi(1) | Priority::Spec(1 | 2) | Priority::Gai(1)
+        )
+    }
+}
+impl FromStr for Priority {
+    type Err = strum::ParseError;
+
+    /// ```
+    /// use jdict2::jmdict::Priority;
+    ///
+    /// assert_eq!("news2".parse::<Priority>(), Ok(Priority::News(2)));
+    /// assert_eq!("nf48".parse::<Priority>(), Ok(Priority::Nf(48)));
+    /// assert!("ichi3".parse::<Priority>().is_err());
+    /// assert!("nf00".parse::<Priority>().is_err());
+    /// assert!("nf1".parse::<Priority>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s
+            .find(|c: char| c.is_ascii_digit())
+            .ok_or(strum::ParseError::VariantNotFound)?;
+        let (kind, rank) = s.split_at(split);
+
+        match (kind, rank) {
+            ("news", "1" | "2") => Ok(Priority::News(rank.parse().unwrap())),
+            ("ichi", "1" | "2") => Ok(Priority::Ichi(rank.parse().unwrap())),
+            ("spec", "1" | "2") => Ok(Priority::Spec(rank.parse().unwrap())),
+            ("gai", "1" | "2") => Ok(Priority::Gai(rank.parse().unwrap())),
+            // Always two digits, nf01 to nf48
+            ("nf", _) if rank.len() == 2 => match rank.parse() {
+                Ok(rank @ 1..=48) => Ok(Priority::Nf(rank)),
+                _ => Err(strum::ParseError::VariantNotFound),
+            },
+            _ => Err(strum::ParseError::VariantNotFound),
+        }
+    }
+}
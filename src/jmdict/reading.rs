@@ -1,3 +1,5 @@
+use super::Priority;
+
 // r_ele
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Reading {
@@ -11,7 +13,6 @@ pub struct Reading {
     #[serde(skip)]
     pub info: Vec<ReadingInfo>,
     // re_pri
-    #[serde(skip)]
     pub prio: Vec<ReadingPrio>,
 }
 
@@ -20,5 +21,4 @@ pub struct Reading {
 pub enum ReadingInfo {}
 
 // re_pri
-#[derive(serde::Serialize, serde::Deserialize)]
-pub enum ReadingPrio {}
+pub type ReadingPrio = Priority;
@@ -1,4 +1,4 @@
-use super::Gloss;
+use super::{CrossReference, Gloss};
 
 // sense
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -6,16 +6,11 @@ pub struct Sense {
     // gloss
     pub glosses: Vec<Gloss>,
     // stagk, stagr, xref, ant, pos, field, misc, dial, pri, s_inf
-    #[serde(skip)]
     pub tags:    Vec<SenseTag>,
     // example
     // pub example: Vec<String>,
 }
 
-// TODO: Parse reference string and build out this struct more
-#[derive(serde::Serialize, serde::Deserialize)]
-pub struct CrossReference(pub compact_str::CompactString);
-
 #[derive(serde::Serialize, serde::Deserialize)]
 pub enum SenseTag {
     // stagk
@@ -48,105 +43,106 @@ pub struct SourceLanguage {
     pub text: compact_str::CompactString,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+// Parsed from the entity name (e.g. "v1"), displayed as its description (e.g. "Ichidan verb")
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[rustfmt::skip]
 pub enum PartOfSpeech {
-    #[strum(serialize = "noun or verb acting prenominally")] AdjF,
-    #[strum(serialize = "adjective (keiyoushi)")] AdjI,
-    #[strum(serialize = "adjective (keiyoushi) - yoi/ii class")] AdjIx,
-    #[strum(serialize = "'kari' adjective (archaic)")] AdjKari,
-    #[strum(serialize = "'ku' adjective (archaic)")] AdjKu,
-    #[strum(serialize = "adjectival nouns or quasi-adjectives (keiyodoshi)")] AdjNa,
-    #[strum(serialize = "archaic/formal form of na-adjective")] AdjNari,
-    #[strum(serialize = "nouns which may take the genitive case particle 'no'")] AdjNo,
-    #[strum(serialize = "pre-noun adjectival (rentaishi)")] AdjPn,
-    #[strum(serialize = "'shiku' adjective (archaic)")] AdjShiku,
-    #[strum(serialize = "'taru' adjective")] AdjT,
-    #[strum(serialize = "adverb (fukushi)")] Adv,
-    #[strum(serialize = "adverb taking the 'to' particle")] AdvTo,
-    #[strum(serialize = "auxiliary")] Aux,
-    #[strum(serialize = "auxiliary adjective")] AuxAdj,
-    #[strum(serialize = "auxiliary verb")] AuxV,
-    #[strum(serialize = "conjunction")] Conj,
-    #[strum(serialize = "copula")] Cop,
-    #[strum(serialize = "counter")] Ctr,
-    #[strum(serialize = "expressions (phrases, clauses, etc.)")] Exp,
-    #[strum(serialize = "interjection (kandoushi)")] Int,
-    #[strum(serialize = "noun (common) (futsuumeishi)")] N,
-    #[strum(serialize = "adverbial noun (fukushitekimeishi)")] NAdv,
-    #[strum(serialize = "proper noun")] NPr,
-    #[strum(serialize = "noun, used as a prefix")] NPref,
-    #[strum(serialize = "noun, used as a suffix")] NSuf,
-    #[strum(serialize = "noun (temporal) (jisoumeishi)")] NT,
-    #[strum(serialize = "numeric")] Num,
-    #[strum(serialize = "pronoun")] Pn,
-    #[strum(serialize = "prefix")] Pref,
-    #[strum(serialize = "particle")] Prt,
-    #[strum(serialize = "suffix")] Suf,
-    #[strum(serialize = "unclassified")] Unc,
-    #[strum(serialize = "verb unspecified")] Vnspec,
-    #[strum(serialize = "Ichidan verb")] V1,
-    #[strum(serialize = "Ichidan verb - kureru special class")] V1S,
-    #[strum(serialize = "Nidan verb with 'u' ending (archaic)")] V2aS,
-    #[strum(serialize = "Nidan verb (upper class) with 'bu' ending (archaic)")] V2bK,
-    #[strum(serialize = "Nidan verb (lower class) with 'bu' ending (archaic)")] V2bS,
-    #[strum(serialize = "Nidan verb (upper class) with 'dzu' ending (archaic)")] V2dK,
-    #[strum(serialize = "Nidan verb (lower class) with 'dzu' ending (archaic)")] V2dS,
-    #[strum(serialize = "Nidan verb (upper class) with 'gu' ending (archaic)")] V2gK,
-    #[strum(serialize = "Nidan verb (lower class) with 'gu' ending (archaic)")] V2gS,
-    #[strum(serialize = "Nidan verb (upper class) with 'hu/fu' ending (archaic)")] V2hK,
-    #[strum(serialize = "Nidan verb (lower class) with 'hu/fu' ending (archaic)")] V2hS,
-    #[strum(serialize = "Nidan verb (upper class) with 'ku' ending (archaic)")] V2kK,
-    #[strum(serialize = "Nidan verb (lower class) with 'ku' ending (archaic)")] V2kS,
-    #[strum(serialize = "Nidan verb (upper class) with 'mu' ending (archaic)")] V2mK,
-    #[strum(serialize = "Nidan verb (lower class) with 'mu' ending (archaic)")] V2mS,
-    #[strum(serialize = "Nidan verb (lower class) with 'nu' ending (archaic)")] V2nS,
-    #[strum(serialize = "Nidan verb (upper class) with 'ru' ending (archaic)")] V2rK,
-    #[strum(serialize = "Nidan verb (lower class) with 'ru' ending (archaic)")] V2rS,
-    #[strum(serialize = "Nidan verb (lower class) with 'su' ending (archaic)")] V2sS,
-    #[strum(serialize = "Nidan verb (upper class) with 'tsu' ending (archaic)")] V2tK,
-    #[strum(serialize = "Nidan verb (lower class) with 'tsu' ending (archaic)")] V2tS,
-    #[strum(serialize = "Nidan verb (lower class) with 'u' ending and 'we' conjugation (archaic)")] V2wS,
-    #[strum(serialize = "Nidan verb (upper class) with 'yu' ending (archaic)")] V2yK,
-    #[strum(serialize = "Nidan verb (lower class) with 'yu' ending (archaic)")] V2yS,
-    #[strum(serialize = "Nidan verb (lower class) with 'zu' ending (archaic)")] V2zS,
-    #[strum(serialize = "Yodan verb with 'bu' ending (archaic)")] V4b,
-    #[strum(serialize = "Yodan verb with 'gu' ending (archaic)")] V4g,
-    #[strum(serialize = "Yodan verb with 'hu/fu' ending (archaic)")] V4h,
-    #[strum(serialize = "Yodan verb with 'ku' ending (archaic)")] V4k,
-    #[strum(serialize = "Yodan verb with 'mu' ending (archaic)")] V4m,
-    #[strum(serialize = "Yodan verb with 'nu' ending (archaic)")] V4n,
-    #[strum(serialize = "Yodan verb with 'ru' ending (archaic)")] V4r,
-    #[strum(serialize = "Yodan verb with 'su' ending (archaic)")] V4s,
-    #[strum(serialize = "Yodan verb with 'tsu' ending (archaic)")] V4t,
-    #[strum(serialize = "Godan verb - -aru special class")] V5aru,
-    #[strum(serialize = "Godan verb with 'bu' ending")] V5b,
-    #[strum(serialize = "Godan verb with 'gu' ending")] V5g,
-    #[strum(serialize = "Godan verb with 'ku' ending")] V5k,
-    #[strum(serialize = "Godan verb - Iku/Yuku special class")] V5kS,
-    #[strum(serialize = "Godan verb with 'mu' ending")] V5m,
-    #[strum(serialize = "Godan verb with 'nu' ending")] V5n,
-    #[strum(serialize = "Godan verb with 'ru' ending")] V5r,
-    #[strum(serialize = "Godan verb with 'ru' ending (irregular verb)")] V5rI,
-    #[strum(serialize = "Godan verb with 'su' ending")] V5s,
-    #[strum(serialize = "Godan verb with 'tsu' ending")] V5t,
-    #[strum(serialize = "Godan verb with 'u' ending")] V5u,
-    #[strum(serialize = "Godan verb with 'u' ending (special class)")] V5uS,
-    #[strum(serialize = "Godan verb - Uru old class verb (old form of Eru)")] V5uru,
-    #[strum(serialize = "intransitive verb")] Vi,
-    #[strum(serialize = "Kuru verb - special class")] Vk,
-    #[strum(serialize = "irregular nu verb")] Vn,
-    #[strum(serialize = "irregular ru verb, plain form ends with -ri")] Vr,
-    #[strum(serialize = "noun or participle which takes the aux. verb suru")] Vs,
-    #[strum(serialize = "su verb - precursor to the modern suru")] VsC,
-    #[strum(serialize = "suru verb - included")] VsI,
-    #[strum(serialize = "suru verb - special class")] VsS,
-    #[strum(serialize = "transitive verb")] Vt,
-    #[strum(serialize = "Ichidan verb - zuru verb (alternative form of -jiru verbs)")] Vz,
+    #[strum(serialize = "adj-f", to_string = "noun or verb acting prenominally")] AdjF,
+    #[strum(serialize = "adj-i", to_string = "adjective (keiyoushi)")] AdjI,
+    #[strum(serialize = "adj-ix", to_string = "adjective (keiyoushi) - yoi/ii class")] AdjIx,
+    #[strum(serialize = "adj-kari", to_string = "'kari' adjective (archaic)")] AdjKari,
+    #[strum(serialize = "adj-ku", to_string = "'ku' adjective (archaic)")] AdjKu,
+    #[strum(serialize = "adj-na", to_string = "adjectival nouns or quasi-adjectives (keiyodoshi)")] AdjNa,
+    #[strum(serialize = "adj-nari", to_string = "archaic/formal form of na-adjective")] AdjNari,
+    #[strum(serialize = "adj-no", to_string = "nouns which may take the genitive case particle 'no'")] AdjNo,
+    #[strum(serialize = "adj-pn", to_string = "pre-noun adjectival (rentaishi)")] AdjPn,
+    #[strum(serialize = "adj-shiku", to_string = "'shiku' adjective (archaic)")] AdjShiku,
+    #[strum(serialize = "adj-t", to_string = "'taru' adjective")] AdjT,
+    #[strum(serialize = "adv", to_string = "adverb (fukushi)")] Adv,
+    #[strum(serialize = "adv-to", to_string = "adverb taking the 'to' particle")] AdvTo,
+    #[strum(serialize = "aux", to_string = "auxiliary")] Aux,
+    #[strum(serialize = "aux-adj", to_string = "auxiliary adjective")] AuxAdj,
+    #[strum(serialize = "aux-v", to_string = "auxiliary verb")] AuxV,
+    #[strum(serialize = "conj", to_string = "conjunction")] Conj,
+    #[strum(serialize = "cop", to_string = "copula")] Cop,
+    #[strum(serialize = "ctr", to_string = "counter")] Ctr,
+    #[strum(serialize = "exp", to_string = "expressions (phrases, clauses, etc.)")] Exp,
+    #[strum(serialize = "int", to_string = "interjection (kandoushi)")] Int,
+    #[strum(serialize = "n", to_string = "noun (common) (futsuumeishi)")] N,
+    #[strum(serialize = "n-adv", to_string = "adverbial noun (fukushitekimeishi)")] NAdv,
+    #[strum(serialize = "n-pr", to_string = "proper noun")] NPr,
+    #[strum(serialize = "n-pref", to_string = "noun, used as a prefix")] NPref,
+    #[strum(serialize = "n-suf", to_string = "noun, used as a suffix")] NSuf,
+    #[strum(serialize = "n-t", to_string = "noun (temporal) (jisoumeishi)")] NT,
+    #[strum(serialize = "num", to_string = "numeric")] Num,
+    #[strum(serialize = "pn", to_string = "pronoun")] Pn,
+    #[strum(serialize = "pref", to_string = "prefix")] Pref,
+    #[strum(serialize = "prt", to_string = "particle")] Prt,
+    #[strum(serialize = "suf", to_string = "suffix")] Suf,
+    #[strum(serialize = "unc", to_string = "unclassified")] Unc,
+    #[strum(serialize = "v-unspec", to_string = "verb unspecified")] Vnspec,
+    #[strum(serialize = "v1", to_string = "Ichidan verb")] V1,
+    #[strum(serialize = "v1-s", to_string = "Ichidan verb - kureru special class")] V1S,
+    #[strum(serialize = "v2a-s", to_string = "Nidan verb with 'u' ending (archaic)")] V2aS,
+    #[strum(serialize = "v2b-k", to_string = "Nidan verb (upper class) with 'bu' ending (archaic)")] V2bK,
+    #[strum(serialize = "v2b-s", to_string = "Nidan verb (lower class) with 'bu' ending (archaic)")] V2bS,
+    #[strum(serialize = "v2d-k", to_string = "Nidan verb (upper class) with 'dzu' ending (archaic)")] V2dK,
+    #[strum(serialize = "v2d-s", to_string = "Nidan verb (lower class) with 'dzu' ending (archaic)")] V2dS,
+    #[strum(serialize = "v2g-k", to_string = "Nidan verb (upper class) with 'gu' ending (archaic)")] V2gK,
+    #[strum(serialize = "v2g-s", to_string = "Nidan verb (lower class) with 'gu' ending (archaic)")] V2gS,
+    #[strum(serialize = "v2h-k", to_string = "Nidan verb (upper class) with 'hu/fu' ending (archaic)")] V2hK,
+    #[strum(serialize = "v2h-s", to_string = "Nidan verb (lower class) with 'hu/fu' ending (archaic)")] V2hS,
+    #[strum(serialize = "v2k-k", to_string = "Nidan verb (upper class) with 'ku' ending (archaic)")] V2kK,
+    #[strum(serialize = "v2k-s", to_string = "Nidan verb (lower class) with 'ku' ending (archaic)")] V2kS,
+    #[strum(serialize = "v2m-k", to_string = "Nidan verb (upper class) with 'mu' ending (archaic)")] V2mK,
+    #[strum(serialize = "v2m-s", to_string = "Nidan verb (lower class) with 'mu' ending (archaic)")] V2mS,
+    #[strum(serialize = "v2n-s", to_string = "Nidan verb (lower class) with 'nu' ending (archaic)")] V2nS,
+    #[strum(serialize = "v2r-k", to_string = "Nidan verb (upper class) with 'ru' ending (archaic)")] V2rK,
+    #[strum(serialize = "v2r-s", to_string = "Nidan verb (lower class) with 'ru' ending (archaic)")] V2rS,
+    #[strum(serialize = "v2s-s", to_string = "Nidan verb (lower class) with 'su' ending (archaic)")] V2sS,
+    #[strum(serialize = "v2t-k", to_string = "Nidan verb (upper class) with 'tsu' ending (archaic)")] V2tK,
+    #[strum(serialize = "v2t-s", to_string = "Nidan verb (lower class) with 'tsu' ending (archaic)")] V2tS,
+    #[strum(serialize = "v2w-s", to_string = "Nidan verb (lower class) with 'u' ending and 'we' conjugation (archaic)")] V2wS,
+    #[strum(serialize = "v2y-k", to_string = "Nidan verb (upper class) with 'yu' ending (archaic)")] V2yK,
+    #[strum(serialize = "v2y-s", to_string = "Nidan verb (lower class) with 'yu' ending (archaic)")] V2yS,
+    #[strum(serialize = "v2z-s", to_string = "Nidan verb (lower class) with 'zu' ending (archaic)")] V2zS,
+    #[strum(serialize = "v4b", to_string = "Yodan verb with 'bu' ending (archaic)")] V4b,
+    #[strum(serialize = "v4g", to_string = "Yodan verb with 'gu' ending (archaic)")] V4g,
+    #[strum(serialize = "v4h", to_string = "Yodan verb with 'hu/fu' ending (archaic)")] V4h,
+    #[strum(serialize = "v4k", to_string = "Yodan verb with 'ku' ending (archaic)")] V4k,
+    #[strum(serialize = "v4m", to_string = "Yodan verb with 'mu' ending (archaic)")] V4m,
+    #[strum(serialize = "v4n", to_string = "Yodan verb with 'nu' ending (archaic)")] V4n,
+    #[strum(serialize = "v4r", to_string = "Yodan verb with 'ru' ending (archaic)")] V4r,
+    #[strum(serialize = "v4s", to_string = "Yodan verb with 'su' ending (archaic)")] V4s,
+    #[strum(serialize = "v4t", to_string = "Yodan verb with 'tsu' ending (archaic)")] V4t,
+    #[strum(serialize = "v5aru", to_string = "Godan verb - -aru special class")] V5aru,
+    #[strum(serialize = "v5b", to_string = "Godan verb with 'bu' ending")] V5b,
+    #[strum(serialize = "v5g", to_string = "Godan verb with 'gu' ending")] V5g,
+    #[strum(serialize = "v5k", to_string = "Godan verb with 'ku' ending")] V5k,
+    #[strum(serialize = "v5k-s", to_string = "Godan verb - Iku/Yuku special class")] V5kS,
+    #[strum(serialize = "v5m", to_string = "Godan verb with 'mu' ending")] V5m,
+    #[strum(serialize = "v5n", to_string = "Godan verb with 'nu' ending")] V5n,
+    #[strum(serialize = "v5r", to_string = "Godan verb with 'ru' ending")] V5r,
+    #[strum(serialize = "v5r-i", to_string = "Godan verb with 'ru' ending (irregular verb)")] V5rI,
+    #[strum(serialize = "v5s", to_string = "Godan verb with 'su' ending")] V5s,
+    #[strum(serialize = "v5t", to_string = "Godan verb with 'tsu' ending")] V5t,
+    #[strum(serialize = "v5u", to_string = "Godan verb with 'u' ending")] V5u,
+    #[strum(serialize = "v5u-s", to_string = "Godan verb with 'u' ending (special class)")] V5uS,
+    #[strum(serialize = "v5uru", to_string = "Godan verb - Uru old class verb (old form of Eru)")] V5uru,
+    #[strum(serialize = "vi", to_string = "intransitive verb")] Vi,
+    #[strum(serialize = "vk", to_string = "Kuru verb - special class")] Vk,
+    #[strum(serialize = "vn", to_string = "irregular nu verb")] Vn,
+    #[strum(serialize = "vr", to_string = "irregular ru verb, plain form ends with -ri")] Vr,
+    #[strum(serialize = "vs", to_string = "noun or participle which takes the aux. verb suru")] Vs,
+    #[strum(serialize = "vs-c", to_string = "su verb - precursor to the modern suru")] VsC,
+    #[strum(serialize = "vs-i", to_string = "suru verb - included")] VsI,
+    #[strum(serialize = "vs-s", to_string = "suru verb - special class")] VsS,
+    #[strum(serialize = "vt", to_string = "transitive verb")] Vt,
+    #[strum(serialize = "vz", to_string = "Ichidan verb - zuru verb (alternative form of -jiru verbs)")] Vz,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumString, strum::Display)]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[rustfmt::skip]
 pub enum Misc {
@@ -165,6 +161,7 @@ pub enum Misc {
     #[strum(serialize = "event")] Event,
     #[strum(serialize = "familiar language")] FamiliarLanguage,
     #[strum(serialize = "female term or language")] Female,
+    #[strum(serialize = "female given name or forename")] FemaleGivenName,
     #[strum(serialize = "fiction")] Fiction,
     #[strum(serialize = "formal or literary term")] Formal,
     #[strum(serialize = "given name or forename, gender not specified")] GivenName,
@@ -177,6 +174,7 @@ pub enum Misc {
     #[strum(serialize = "legend")] Legend,
     #[strum(serialize = "manga slang")] MangaSlang,
     #[strum(serialize = "male term or language")] Male,
+    #[strum(serialize = "male given name or forename")] MaleGivenName,
     #[strum(serialize = "mythology")] Mythology,
     #[strum(serialize = "Internet slang")] InternetSlang,
     #[strum(serialize = "object")] Object,
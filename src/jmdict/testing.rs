@@ -0,0 +1,57 @@
+use super::{
+    Entry,
+    EntrySeq,
+    Gloss,
+    GlossType,
+    Kanji,
+    PartOfSpeech,
+    Priority,
+    Reading,
+    Sense,
+    SenseTag,
+};
+
+/// An entry with one sense, and a kanji element unless `kanji` is empty, for tests.
+pub(crate) fn entry(ent_seq: u32, kanji: &str, reading: &str, gloss: &str) -> Entry {
+    Entry {
+        ent_seq: EntrySeq(ent_seq),
+        kanji:   match kanji {
+            "" => Vec::new(),
+            _ => vec![Kanji {
+                text: kanji.into(),
+                info: Vec::new(),
+                prio: Vec::new(),
+            }],
+        },
+        reading: vec![Reading {
+            text: reading.into(),
+            no_kanji: false,
+            restrict_to_kanji: Vec::new(),
+            info: Vec::new(),
+            prio: Vec::new(),
+        }],
+        sense:   vec![Sense {
+            glosses: vec![Gloss {
+                text: gloss.into(),
+                lang: isolang::Language::Eng,
+                highlight: false,
+                typ: GlossType::Regular,
+                gender: None,
+            }],
+            tags:    Vec::new(),
+        }],
+    }
+}
+
+impl Entry {
+    /// Mark the reading as a common word.
+    pub(crate) fn common(mut self) -> Self {
+        self.reading[0].prio.push(Priority::Ichi(1));
+        self
+    }
+
+    pub(crate) fn part_of_speech(mut self, pos: PartOfSpeech) -> Self {
+        self.sense[0].tags.push(SenseTag::PartOfSpeech(pos));
+        self
+    }
+}
@@ -0,0 +1,54 @@
+pub mod parsing;
+
+use compact_str::CompactString;
+pub use parsing::{parse_jmnedict, parse_jmnedict_stream};
+
+use crate::jmdict::{CrossReference, EntrySeq, Misc};
+use crate::search_index::SearchIndex;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct NameDictionary {
+    pub entries: Vec<NameEntry>,
+    pub search_index: SearchIndex,
+}
+impl NameDictionary {
+    pub fn new(entries: Vec<NameEntry>) -> Self {
+        let search_index = SearchIndex::build_from_texts(entries.iter().map(|entry| {
+            entry
+                .kanji
+                .iter()
+                .chain(&entry.reading)
+                .chain(entry.translations.iter().flat_map(|t| &t.details))
+                .map(|text| text.as_str())
+        }));
+
+        NameDictionary {
+            entries,
+            search_index,
+        }
+    }
+}
+
+// entry
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct NameEntry {
+    // ent_seq
+    pub ent_seq: EntrySeq,
+    // k_ele/keb
+    pub kanji: Vec<CompactString>,
+    // r_ele/reb
+    pub reading: Vec<CompactString>,
+    // trans
+    pub translations: Vec<Translation>,
+}
+
+// trans
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Translation {
+    // name_type, shares its values with <misc> in JMdict
+    pub name_types: Vec<Misc>,
+    // xref
+    pub see_also:   Vec<CrossReference>,
+    // trans_det
+    pub details:    Vec<CompactString>,
+}
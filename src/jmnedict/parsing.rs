@@ -0,0 +1,233 @@
+use std::io::BufRead;
+
+use roxmltree::NodeType;
+
+use super::{NameEntry, Translation};
+use crate::jmdict::parsing::EntityNames;
+use crate::jmdict::EntrySeq;
+use crate::parse_error::{ParseContext, ParseError, ParseErrorKind};
+use crate::xml_stream::XmlStream;
+
+pub fn parse_jmnedict(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Vec<NameEntry>, ParseError> {
+    if node.tag_name().name() != "JMnedict" {
+        return Err(ctx.error(
+            node,
+            ParseErrorKind::UnexpectedElement(node.tag_name().name().into()),
+        ));
+    }
+
+    let entities = EntityNames::from_dtd(node.document().input_text());
+
+    let mut result = Vec::new();
+
+    for child in node.children() {
+        match (child.node_type(), child.tag_name().name()) {
+            (NodeType::Element, "entry") => result.push(parse_entry(child, &entities, ctx)?),
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
+        }
+    }
+
+    Ok(result)
+}
+
+/// Like [`parse_jmnedict`], but reads the document one `<entry>` at a time instead of loading it as a whole.
+pub fn parse_jmnedict_stream(
+    reader: impl BufRead,
+    ctx: &mut ParseContext,
+) -> Result<Vec<NameEntry>, ParseError> {
+    let mut stream = XmlStream::new(reader)?;
+    stream.expect_root("JMnedict")?;
+
+    let prolog = stream.prolog().to_owned();
+    let entities = EntityNames::from_dtd(&prolog);
+
+    let mut result = Vec::new();
+
+    stream.for_each_element(ctx, |node, ctx| {
+        match node.tag_name().name() {
+            "entry" => result.push(parse_entry(node, &entities, ctx)?),
+            _ => ctx.unexpected(node)?,
+        }
+        Ok(())
+    })?;
+
+    Ok(result)
+}
+
+fn parse_entry(
+    node: roxmltree::Node,
+    entities: &EntityNames,
+    ctx: &mut ParseContext,
+) -> Result<NameEntry, ParseError> {
+    assert_eq!(node.tag_name().name(), "entry");
+
+    let mut result = NameEntry {
+        ent_seq: EntrySeq::INVALID,
+        kanji: Vec::new(),
+        reading: Vec::new(),
+        translations: Vec::new(),
+    };
+
+    for child in node.children() {
+        match (child.node_type(), child.tag_name().name()) {
+            (NodeType::Element, "ent_seq") => {
+                if let Some(ent_seq) = ctx.parse_text(child)? {
+                    result.ent_seq = ent_seq;
+                }
+            }
+            (NodeType::Element, "k_ele") => {
+                // ke_inf and ke_pri are unused in JMnedict
+                if let Some(keb) = child.children().find(|c| c.has_tag_name("keb")) {
+                    result.kanji.extend(ctx.text(keb)?.map(Into::into));
+                }
+            }
+            (NodeType::Element, "r_ele") => {
+                // re_restr, re_inf and re_pri are unused in JMnedict
+                if let Some(reb) = child.children().find(|c| c.has_tag_name("reb")) {
+                    result.reading.extend(ctx.text(reb)?.map(Into::into));
+                }
+            }
+            (NodeType::Element, "trans") => {
+                result.translations.push(parse_trans(child, entities, ctx)?);
+            }
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
+        }
+    }
+
+    Ok(result)
+}
+
+fn parse_trans(
+    node: roxmltree::Node,
+    entities: &EntityNames,
+    ctx: &mut ParseContext,
+) -> Result<Translation, ParseError> {
+    assert_eq!(node.tag_name().name(), "trans");
+
+    let mut result = Translation {
+        name_types: Vec::new(),
+        see_also:   Vec::new(),
+        details:    Vec::new(),
+    };
+
+    for child in node.children() {
+        match (child.node_type(), child.tag_name().name()) {
+            (NodeType::Element, "name_type") => {
+                if let Some(name_type) = ctx.parse_text_with(child, |text| entities.parse(text))? {
+                    result.name_types.push(name_type);
+                }
+            }
+            (NodeType::Element, "xref") => {
+                if let Some(xref) = ctx.parse_text(child)? {
+                    result.see_also.push(xref);
+                }
+            }
+            (NodeType::Element, "trans_det") => {
+                // Only English in the current JMnedict, so xml:lang is ignored
+                result.details.extend(ctx.text(child)?.map(Into::into));
+            }
+            (NodeType::Text | NodeType::Comment, _) => {}
+            _ => ctx.unexpected(child)?,
+        }
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::jmdict::{EntrySeq, Misc};
+    use crate::parse_error::{ParseContext, ParseMode};
+
+    #[test]
+    fn test_parse_entry() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMnedict [
+<!ENTITY surname "family or surname">
+<!ENTITY place "place name">
+]>
+<JMnedict>
+<entry>
+<ent_seq>5000001</ent_seq>
+<k_ele><keb>田中</keb></k_ele>
+<r_ele><reb>たなか</reb></r_ele>
+<trans><name_type>&surname;</name_type><name_type>&place;</name_type><trans_det>Tanaka</trans_det></trans>
+</entry>
+<entry>
+<ent_seq>5000002</ent_seq>
+<r_ele><reb>ウエノ</reb></r_ele>
+<trans><name_type>&place;</name_type><xref>上野・うえの</xref><trans_det>Ueno</trans_det><trans_det xml:lang="eng">Ueno (Tokyo)</trans_det></trans>
+</entry>
+</JMnedict>"#;
+        let document = roxmltree::Document::parse_with_options(xml, roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let entries = super::parse_jmnedict(document.root_element(), &mut ctx).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].ent_seq, EntrySeq(5000001));
+        assert_eq!(entries[0].kanji, ["田中"]);
+        assert_eq!(entries[0].reading, ["たなか"]);
+        assert_eq!(entries[0].translations[0].name_types, [
+            Misc::Surname,
+            Misc::Place
+        ]);
+        assert_eq!(entries[0].translations[0].details, ["Tanaka"]);
+
+        // Kana-only
+        assert!(entries[1].kanji.is_empty());
+        let translation = &entries[1].translations[0];
+        assert_eq!(translation.see_also[0].text, "上野");
+        assert_eq!(translation.see_also[0].reading.as_deref(), Some("うえの"));
+        assert_eq!(translation.details, ["Ueno", "Ueno (Tokyo)"]);
+    }
+
+    #[test]
+    fn test_parse_stream() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMnedict [
+<!ELEMENT JMnedict (entry*)>
+<!ENTITY surname "family or surname">
+<!ENTITY place "place name">
+]>
+<JMnedict>
+<!-- JMnedict created: 2024-01-01 -->
+<entry>
+<ent_seq>5000001</ent_seq>
+<k_ele><keb>田中</keb></k_ele>
+<r_ele><reb>たなか</reb></r_ele>
+<trans><name_type>&surname;</name_type><trans_det>Tanaka</trans_det></trans>
+</entry>
+<entry>
+<ent_seq>5000002</ent_seq>
+<r_ele><reb>ウエノ</reb></r_ele>
+<trans><name_type>&place;</name_type><xref>上野・うえの</xref><trans_det>Ueno &amp; surroundings</trans_det></trans>
+</entry>
+</JMnedict>"#;
+        let document = roxmltree::Document::parse_with_options(xml, roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let entries = super::parse_jmnedict(document.root_element(), &mut ctx).unwrap();
+        let streamed = super::parse_jmnedict_stream(xml.as_bytes(), &mut ctx).unwrap();
+
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed[1].translations[0].name_types, [Misc::Place]);
+        assert_eq!(
+            postcard::to_allocvec(&entries).unwrap(),
+            postcard::to_allocvec(&streamed).unwrap()
+        );
+    }
+}
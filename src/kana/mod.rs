@@ -105,6 +105,29 @@ pub fn romaji_to(ty: KanaType, text: &str) -> (u32, String) {
     (failures, result)
 }
 
+/// Replace voiced and semi-voiced kana by their unvoiced forms, e.g. to compare readings that only differ by rendaku.
+/// ```
+/// use jdict2::kana::strip_voicing;
+///
+/// assert_eq!(strip_voicing("ばんごう"), "はんこう");
+/// assert_eq!(strip_voicing("パン"), "ハン");
+/// assert_eq!(strip_voicing("ヴ漢"), "ウ漢");
+/// ```
+pub fn strip_voicing(text: &str) -> String {
+    const VOICED: &str = "がぎぐげござじずぜぞだぢづでどばびぶべぼぱぴぷぺぽゔガギグゲゴザジズゼゾダヂヅデドバビブベボパピプペポヴ";
+    const UNVOICED: &str = "かきくけこさしすせそたちつてとはひふへほはひふへほうカキクケコサシスセソタチツテトハヒフヘホハヒフヘホウ";
+
+    text.chars()
+        .map(|c| {
+            VOICED
+                .chars()
+                .zip(UNVOICED.chars())
+                .find(|(voiced, _)| *voiced == c)
+                .map_or(c, |(_, unvoiced)| unvoiced)
+        })
+        .collect()
+}
+
 fn snip_prefix(prefix: &str, text: &mut &str) -> bool {
     if let Some(s) = text.strip_prefix(prefix) {
         *text = s;
@@ -0,0 +1,44 @@
+use compact_str::CompactString;
+
+// dic_ref
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct DictionaryReference {
+    // dr_type
+    pub dictionary: Dictionary,
+    // Usually the index number, but some dictionaries use other schemes (e.g. "3.18" for lesson 3)
+    pub index: CompactString,
+    // m_vol, m_page, only for Morohashi
+    pub volume: Option<u8>,
+    pub page: Option<u16>,
+}
+
+// dic_ref dr_type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, strum::Display, strum::EnumIter)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[rustfmt::skip]
+pub enum Dictionary {
+    #[strum(serialize = "nelson_c",         to_string = "Nelson (Classic)")]                NelsonClassic,
+    #[strum(serialize = "nelson_n",         to_string = "Nelson (New)")]                    NelsonNew,
+    #[strum(serialize = "halpern_njecd",    to_string = "Halpern NJECD")]                   HalpernNjecd,
+    #[strum(serialize = "halpern_kkd",      to_string = "Halpern Kodansha Kanji Dictionary")] HalpernKkd,
+    #[strum(serialize = "halpern_kkld",     to_string = "Halpern Kanji Learner's Dictionary")] HalpernKkld,
+    #[strum(serialize = "halpern_kkld_2ed", to_string = "Halpern Kanji Learner's Dictionary (2nd ed.)")] HalpernKkld2,
+    #[strum(serialize = "heisig",           to_string = "Heisig")]                          Heisig,
+    #[strum(serialize = "heisig6",          to_string = "Heisig (6th ed.)")]                Heisig6,
+    #[strum(serialize = "gakken",           to_string = "Gakken")]                          Gakken,
+    #[strum(serialize = "oneill_names",     to_string = "O'Neill Japanese Names")]          ONeillNames,
+    #[strum(serialize = "oneill_kk",        to_string = "O'Neill Essential Kanji")]         ONeillKk,
+    #[strum(serialize = "moro",             to_string = "Morohashi")]                       Moro,
+    #[strum(serialize = "henshall",         to_string = "Henshall")]                        Henshall,
+    #[strum(serialize = "sh_kk",            to_string = "Spahn-Hadamitzky Kanji & Kana")]   ShKk,
+    #[strum(serialize = "sh_kk2",           to_string = "Spahn-Hadamitzky Kanji & Kana (2011)")] ShKk2,
+    #[strum(serialize = "sakade",           to_string = "Sakade")]                          Sakade,
+    #[strum(serialize = "jf_cards",         to_string = "Japanese Kanji Flashcards")]       JfCards,
+    #[strum(serialize = "henshall3",        to_string = "Henshall (3rd ed.)")]              Henshall3,
+    #[strum(serialize = "tutt_cards",       to_string = "Tuttle Kanji Cards")]              TuttCards,
+    #[strum(serialize = "crowley",          to_string = "Crowley")]                         Crowley,
+    #[strum(serialize = "kanji_in_context", to_string = "Kanji in Context")]                KanjiInContext,
+    #[strum(serialize = "busy_people",      to_string = "Japanese for Busy People")]        BusyPeople,
+    #[strum(serialize = "kodansha_compact", to_string = "Kodansha Compact Kanji Guide")]    KodanshaCompact,
+    #[strum(serialize = "maniette",         to_string = "Maniette")]                        Maniette,
+}
@@ -1,8 +1,13 @@
+pub mod dic_number;
 pub mod parsing;
+pub mod query_code;
 pub mod reading_meaning;
+#[cfg(test)] pub(crate) mod testing;
 
 use compact_str::CompactString;
-pub use parsing::parse_kanjidic2;
+pub use dic_number::*;
+pub use parsing::{parse_kanjidic2, parse_kanjidic2_stream};
+pub use query_code::*;
 pub use reading_meaning::*;
 use smallvec::SmallVec;
 
@@ -29,13 +34,124 @@ pub struct Character {
     // misc
     pub misc: CharacterMetadata,
     // dic_number
-    pub dic_number: (), // TODO
+    pub dic_number: Vec<DictionaryReference>,
     // query_code
-    pub query_code: (), // TODO
+    pub query_code: Vec<QueryCode>,
     // reading_meaning
     pub reading_meaning: SmallVec<[ReadingMeaning; 1]>,
 }
+impl Character {
+    /// Index of this kanji in the given dictionary, e.g. its Heisig number.
+    pub fn dictionary_index(&self, dictionary: Dictionary) -> Option<&str> {
+        self.dic_number
+            .iter()
+            .find(|r| r.dictionary == dictionary)
+            .map(|r| r.index.as_str())
+    }
+
+    /// All on'yomi of this kanji, in katakana, without duplicates.
+    pub fn onyomi(&self) -> Vec<&str> {
+        let mut onyomi: Vec<&str> = Vec::new();
+        for reading in self
+            .reading_meaning
+            .iter()
+            .flat_map(|rm| &rm.reading_meaning_groups)
+            .flat_map(|rmg| &rmg.readings)
+            .filter(|r| matches!(r.typ, ReadingType::Onyomi(_)))
+        {
+            if !onyomi.contains(&reading.value.as_str()) {
+                onyomi.push(&reading.value);
+            }
+        }
+        onyomi
+    }
+
+    /// The correct SKIP codes, without misclassifications.
+    pub fn skip_codes(&self) -> impl Iterator<Item = SkipCode> + '_ {
+        self.query_code.iter().filter_map(|code| match code {
+            QueryCode::Skip(skip) => Some(*skip),
+            _ => None,
+        })
+    }
+
+    /// Whether this kanji has the SKIP code `skip`, or is commonly misclassified as it if `misclassifications` is set.
+    pub fn has_skip_code(&self, skip: SkipCode, misclassifications: bool) -> bool {
+        self.query_code.iter().any(|code| match code {
+            QueryCode::Skip(code) => *code == skip,
+            QueryCode::SkipMisclassification(code, _) => misclassifications && *code == skip,
+            _ => false,
+        })
+    }
+}
 
 // misc
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct CharacterMetadata {}
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct CharacterMetadata {
+    // grade
+    // 1-6: Kyouiku kanji taught in that grade of elementary school, 8: remaining Jouyou kanji, 9-10: Jinmeiyou kanji
+    pub grade: Option<u8>,
+    // stroke_count
+    // The first one is the accepted count, any others are common miscounts
+    pub stroke_counts: SmallVec<[u8; 1]>,
+    // variant
+    pub variants: Vec<Variant>,
+    // freq
+    // Rank among the 2500 most used kanji in newspapers
+    pub frequency: Option<u16>,
+    // rad_name
+    // Only for kanji that are radicals themselves
+    pub radical_names: Vec<CompactString>,
+    // jlpt
+    // Level in the pre-2010 JLPT (1-4), which is what kanjidic2 still uses
+    pub jlpt: Option<u8>,
+}
+impl CharacterMetadata {
+    pub fn stroke_count(&self) -> Option<u8> { self.stroke_counts.first().copied() }
+
+    /// Stroke counts people commonly arrive at, which aren't the accepted one.
+    pub fn miscounts(&self) -> &[u8] { self.stroke_counts.get(1..).unwrap_or_default() }
+}
+
+// variant
+// Another character this one is a variant of, given as a code in one of the dictionaries or encodings
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Variant {
+    pub typ:   VariantType,
+    pub value: CompactString,
+}
+impl Variant {
+    /// The variant as a character, if it is given as a unicode codepoint.
+    /// ```
+    /// use jdict2::kanjidic2::{Variant, VariantType};
+    ///
+    /// let variant = Variant {
+    ///     typ:   VariantType::Ucs,
+    ///     value: "5ee3".into(),
+    /// };
+    /// assert_eq!(variant.character(), Some('廣'));
+    /// ```
+    pub fn character(&self) -> Option<char> {
+        match self.typ {
+            VariantType::Ucs => u32::from_str_radix(&self.value, 16)
+                .ok()
+                .and_then(char::from_u32),
+            _ => None,
+        }
+    }
+}
+
+// variant var_type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, strum::Display)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[rustfmt::skip]
+pub enum VariantType {
+    #[strum(serialize = "jis208")]   Jis208,
+    #[strum(serialize = "jis212")]   Jis212,
+    #[strum(serialize = "jis213")]   Jis213,
+    #[strum(serialize = "deroo")]    DeRoo,
+    #[strum(serialize = "njecd")]    Njecd,
+    #[strum(serialize = "s_h")]      SpahnHadamitzky,
+    #[strum(serialize = "nelson_c")] NelsonClassic,
+    #[strum(serialize = "oneill")]   ONeill,
+    #[strum(serialize = "ucs")]      Ucs,
+}
@@ -1,11 +1,23 @@
+use std::io::BufRead;
+
 use roxmltree::NodeType;
 use smallvec::SmallVec;
 
 use super::reading_meaning::{Meaning, Reading, ReadingMeaning, ReadingMeaningGroup, ReadingType};
-use super::{Character, CharacterMetadata, Header};
+use super::{Character, CharacterMetadata, DictionaryReference, Header, QueryCode, Variant};
+use crate::parse_error::{ParseContext, ParseError, ParseErrorKind};
+use crate::xml_stream::XmlStream;
 
-pub fn parse_kanjidic2(xml: roxmltree::Node) -> (Header, Vec<Character>) {
-    assert_eq!(xml.tag_name().name(), "kanjidic2");
+pub fn parse_kanjidic2(
+    xml: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<(Header, Vec<Character>), ParseError> {
+    if xml.tag_name().name() != "kanjidic2" {
+        return Err(ctx.error(
+            xml,
+            ParseErrorKind::UnexpectedElement(xml.tag_name().name().into()),
+        ));
+    }
 
     let mut header = Header {
         file_version:     "".into(),
@@ -16,17 +28,44 @@ pub fn parse_kanjidic2(xml: roxmltree::Node) -> (Header, Vec<Character>) {
 
     for node in xml.children() {
         match (node.node_type(), node.tag_name().name()) {
-            (NodeType::Element, "header") => header = parse_header(node),
-            (NodeType::Element, "character") => characters.push(parse_character(node)),
+            (NodeType::Element, "header") => header = parse_header(node, ctx)?,
+            (NodeType::Element, "character") => characters.push(parse_character(node, ctx)?),
             (NodeType::Text | NodeType::Comment, _) => (),
-            (ty, name) => panic!("Unexpected child in <kanjidic2>: {:?} {}", ty, name),
+            _ => ctx.unexpected(node)?,
         }
     }
 
-    (header, characters)
+    Ok((header, characters))
+}
+
+/// Like [`parse_kanjidic2`], but reads the document one `<character>` at a time instead of loading it as a whole.
+pub fn parse_kanjidic2_stream(
+    reader: impl BufRead,
+    ctx: &mut ParseContext,
+) -> Result<(Header, Vec<Character>), ParseError> {
+    let mut stream = XmlStream::new(reader)?;
+    stream.expect_root("kanjidic2")?;
+
+    let mut header = Header {
+        file_version:     "".into(),
+        database_version: "".into(),
+        date_of_creation: "".into(),
+    };
+    let mut characters = Vec::new();
+
+    stream.for_each_element(ctx, |node, ctx| {
+        match node.tag_name().name() {
+            "header" => header = parse_header(node, ctx)?,
+            "character" => characters.push(parse_character(node, ctx)?),
+            _ => ctx.unexpected(node)?,
+        }
+        Ok(())
+    })?;
+
+    Ok((header, characters))
 }
 
-fn parse_header(node: roxmltree::Node) -> Header {
+fn parse_header(node: roxmltree::Node, ctx: &mut ParseContext) -> Result<Header, ParseError> {
     assert_eq!(node.tag_name().name(), "header");
 
     let mut header = Header {
@@ -38,32 +77,32 @@ fn parse_header(node: roxmltree::Node) -> Header {
     for node in node.children() {
         match (node.node_type(), node.tag_name().name()) {
             (NodeType::Element, "file_version") => {
-                header.file_version = node.text().unwrap().into()
+                header.file_version = ctx.text(node)?.unwrap_or_default().into()
             }
             (NodeType::Element, "database_version") => {
-                header.database_version = node.text().unwrap().into()
+                header.database_version = ctx.text(node)?.unwrap_or_default().into()
             }
             (NodeType::Element, "date_of_creation") => {
-                header.date_of_creation = node.text().unwrap().into()
+                header.date_of_creation = ctx.text(node)?.unwrap_or_default().into()
             }
             (NodeType::Text | NodeType::Comment, _) => (),
-            (ty, name) => panic!("Unexpected child in <header>: {:?} {}", ty, name),
+            _ => ctx.unexpected(node)?,
         }
     }
 
-    header
+    Ok(header)
 }
 
-fn parse_character(node: roxmltree::Node) -> Character {
+fn parse_character(node: roxmltree::Node, ctx: &mut ParseContext) -> Result<Character, ParseError> {
     assert_eq!(node.tag_name().name(), "character");
 
     let mut character = Character {
         unicode: ' ',
         radicals: SmallVec::new(),
         radicals_nelson_c: None,
-        misc: CharacterMetadata {},
-        dic_number: (),
-        query_code: (),
+        misc: CharacterMetadata::default(),
+        dic_number: Vec::new(),
+        query_code: Vec::new(),
         reading_meaning: SmallVec::new(),
     };
 
@@ -74,17 +113,20 @@ fn parse_character(node: roxmltree::Node) -> Character {
                 for node in node.children() {
                     match (node.node_type(), node.tag_name().name()) {
                         (NodeType::Element, "cp_value") => {
-                            let cp_type = node.attribute("cp_type").unwrap();
-                            let value = node.text().unwrap();
-                            if cp_type == "ucs" {
-                                let value = u32::from_str_radix(value, 16).unwrap();
-                                character.unicode = std::char::from_u32(value).unwrap()
+                            if ctx.attribute(node, "cp_type")? == Some("ucs") {
+                                let unicode = ctx.parse_text_with(node, |value| {
+                                    u32::from_str_radix(value, 16)
+                                        .ok()
+                                        .and_then(char::from_u32)
+                                        .ok_or("not a codepoint")
+                                })?;
+                                if let Some(unicode) = unicode {
+                                    character.unicode = unicode;
+                                }
                             }
                         }
-                        (NodeType::Text, _) => (),
-                        (ty, name) => {
-                            panic!("Unexpected child in <codepoint>: {:?} {}", ty, name)
-                        }
+                        (NodeType::Text | NodeType::Comment, _) => (),
+                        _ => ctx.unexpected(node)?,
                     }
                 }
             }
@@ -92,52 +134,183 @@ fn parse_character(node: roxmltree::Node) -> Character {
                 for node in node.children() {
                     match (node.node_type(), node.tag_name().name()) {
                         (NodeType::Element, "rad_value") => {
-                            let rad_type = node.attribute("rad_type").unwrap();
-                            let value = node.text().unwrap();
+                            let Some(rad_type) = ctx.attribute(node, "rad_type")?
+                            else {
+                                continue;
+                            };
+                            let Some(value) = ctx.parse_text(node)?
+                            else {
+                                continue;
+                            };
                             match rad_type {
-                                "classical" => character.radicals.push(value.parse().unwrap()),
-                                "nelson_c" => {
-                                    if character.radicals_nelson_c.is_none() {
-                                        character.radicals_nelson_c = Some(SmallVec::new());
-                                    }
-                                    character
-                                        .radicals_nelson_c
-                                        .get_or_insert_with(SmallVec::new)
-                                        .push(value.parse().unwrap());
-                                }
-                                _ => panic!("Unexpected rad_type '{}'", rad_type),
+                                "classical" => character.radicals.push(value),
+                                "nelson_c" => character
+                                    .radicals_nelson_c
+                                    .get_or_insert_with(SmallVec::new)
+                                    .push(value),
+                                _ => ctx.invalid(node, rad_type, "unknown rad_type")?,
                             }
                         }
-                        (NodeType::Text, _) => (),
-                        (ty, name) => {
-                            panic!("Unexpected child in <radical>: {:?} {}", ty, name)
+                        (NodeType::Text | NodeType::Comment, _) => (),
+                        _ => ctx.unexpected(node)?,
+                    }
+                }
+            }
+            (NodeType::Element, "misc") => character.misc = parse_character_metadata(node, ctx)?,
+            (NodeType::Element, "dic_number") => {
+                for node in node.children() {
+                    match (node.node_type(), node.tag_name().name()) {
+                        (NodeType::Element, "dic_ref") => {
+                            character.dic_number.extend(parse_dic_ref(node, ctx)?)
                         }
+                        (NodeType::Text | NodeType::Comment, _) => (),
+                        _ => ctx.unexpected(node)?,
                     }
                 }
             }
-            (NodeType::Element, "misc") => character.misc = parse_character_metadata(node),
-            (NodeType::Element, "dic_number") => (),
-            (NodeType::Element, "query_code") => (),
-            (NodeType::Element, "reading_meaning") => {
-                character.reading_meaning.push(parse_reading_meaning(node))
+            (NodeType::Element, "query_code") => {
+                for node in node.children() {
+                    match (node.node_type(), node.tag_name().name()) {
+                        (NodeType::Element, "q_code") => {
+                            character.query_code.extend(parse_q_code(node, ctx)?)
+                        }
+                        (NodeType::Text | NodeType::Comment, _) => (),
+                        _ => ctx.unexpected(node)?,
+                    }
+                }
             }
-            (NodeType::Text, _) => (),
-            (ty, name) => panic!("Unexpected child in <character>: {:?} {}", ty, name),
+            (NodeType::Element, "reading_meaning") => character
+                .reading_meaning
+                .push(parse_reading_meaning(node, ctx)?),
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
         }
     }
 
-    character
+    Ok(character)
 }
 
-fn parse_character_metadata(node: roxmltree::Node) -> CharacterMetadata {
+fn parse_character_metadata(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<CharacterMetadata, ParseError> {
     assert_eq!(node.tag_name().name(), "misc");
 
-    // TODO
+    let mut misc = CharacterMetadata::default();
+
+    for node in node.children() {
+        match (node.node_type(), node.tag_name().name()) {
+            (NodeType::Element, "grade") => misc.grade = ctx.parse_text(node)?,
+            (NodeType::Element, "stroke_count") => {
+                if let Some(stroke_count) = ctx.parse_text(node)? {
+                    misc.stroke_counts.push(stroke_count);
+                }
+            }
+            (NodeType::Element, "variant") => {
+                let Some(var_type) = ctx.attribute(node, "var_type")?
+                else {
+                    continue;
+                };
+                let Some(typ) = ctx.parse_value(node, var_type, str::parse)?
+                else {
+                    continue;
+                };
+                if let Some(value) = ctx.text(node)? {
+                    misc.variants.push(Variant {
+                        typ,
+                        value: value.into(),
+                    });
+                }
+            }
+            (NodeType::Element, "freq") => misc.frequency = ctx.parse_text(node)?,
+            (NodeType::Element, "rad_name") => {
+                if let Some(text) = ctx.text(node)? {
+                    misc.radical_names.push(text.into());
+                }
+            }
+            (NodeType::Element, "jlpt") => misc.jlpt = ctx.parse_text(node)?,
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
+        }
+    }
 
-    CharacterMetadata {}
+    Ok(misc)
 }
 
-fn parse_reading_meaning(node: roxmltree::Node) -> ReadingMeaning {
+fn parse_dic_ref(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Option<DictionaryReference>, ParseError> {
+    assert_eq!(node.tag_name().name(), "dic_ref");
+
+    let Some(dr_type) = ctx.attribute(node, "dr_type")?
+    else {
+        return Ok(None);
+    };
+    let Some(dictionary) = ctx.parse_value(node, dr_type, str::parse)?
+    else {
+        return Ok(None);
+    };
+    let Some(index) = ctx.text(node)?
+    else {
+        return Ok(None);
+    };
+
+    let volume = match node.attribute("m_vol") {
+        Some(m_vol) => ctx.parse_value(node, m_vol, str::parse)?,
+        None => None,
+    };
+    let page = match node.attribute("m_page") {
+        Some(m_page) => ctx.parse_value(node, m_page, str::parse)?,
+        None => None,
+    };
+
+    Ok(Some(DictionaryReference {
+        dictionary,
+        index: index.into(),
+        volume,
+        page,
+    }))
+}
+
+fn parse_q_code(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Option<QueryCode>, ParseError> {
+    assert_eq!(node.tag_name().name(), "q_code");
+
+    let Some(qc_type) = ctx.attribute(node, "qc_type")?
+    else {
+        return Ok(None);
+    };
+
+    Ok(match qc_type {
+        "skip" => {
+            let Some(skip) = ctx.parse_text(node)?
+            else {
+                return Ok(None);
+            };
+            match node.attribute("skip_misclass") {
+                Some(misclass) => ctx
+                    .parse_value(node, misclass, str::parse)?
+                    .map(|misclass| QueryCode::SkipMisclassification(skip, misclass)),
+                None => Some(QueryCode::Skip(skip)),
+            }
+        }
+        "sh_desc" => ctx.parse_text(node)?.map(QueryCode::SpahnHadamitzky),
+        "four_corner" => ctx.parse_text(node)?.map(QueryCode::FourCorner),
+        "deroo" => ctx.parse_text(node)?.map(QueryCode::DeRoo),
+        _ => {
+            ctx.invalid(node, qc_type, "unknown qc_type")?;
+            None
+        }
+    })
+}
+
+fn parse_reading_meaning(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<ReadingMeaning, ParseError> {
     assert_eq!(node.tag_name().name(), "reading_meaning");
 
     let mut nanori = Vec::new();
@@ -146,23 +319,28 @@ fn parse_reading_meaning(node: roxmltree::Node) -> ReadingMeaning {
     for node in node.children() {
         match (node.node_type(), node.tag_name().name()) {
             (NodeType::Element, "nanori") => {
-                nanori.push(node.text().unwrap().into());
+                if let Some(text) = ctx.text(node)? {
+                    nanori.push(text.into());
+                }
             }
             (NodeType::Element, "rmgroup") => {
-                reading_meaning_groups.push(parse_reading_meaning_group(node));
+                reading_meaning_groups.push(parse_reading_meaning_group(node, ctx)?);
             }
-            (NodeType::Text, _) => (),
-            (ty, name) => panic!("Unexpected child in <reading_meaning>: {:?} {}", ty, name),
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
         }
     }
 
-    ReadingMeaning {
+    Ok(ReadingMeaning {
         nanori,
         reading_meaning_groups,
-    }
+    })
 }
 
-fn parse_reading_meaning_group(node: roxmltree::Node) -> ReadingMeaningGroup {
+fn parse_reading_meaning_group(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<ReadingMeaningGroup, ParseError> {
     assert_eq!(node.tag_name().name(), "rmgroup");
 
     let mut readings = Vec::new();
@@ -170,44 +348,188 @@ fn parse_reading_meaning_group(node: roxmltree::Node) -> ReadingMeaningGroup {
 
     for node in node.children() {
         match (node.node_type(), node.tag_name().name()) {
-            (NodeType::Element, "reading") => readings.push(parse_reading(node)),
-            (NodeType::Element, "meaning") => meanings.push(parse_meaning(node)),
-            (NodeType::Text, _) => (),
-            (ty, name) => panic!("Unexpected child in <rmgroup>: {:?} {}", ty, name),
+            (NodeType::Element, "reading") => readings.extend(parse_reading(node, ctx)?),
+            (NodeType::Element, "meaning") => meanings.extend(parse_meaning(node, ctx)?),
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
         }
     }
 
-    ReadingMeaningGroup { readings, meanings }
+    Ok(ReadingMeaningGroup { readings, meanings })
 }
 
-fn parse_reading(node: roxmltree::Node) -> Reading {
+fn parse_reading(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Option<Reading>, ParseError> {
     assert_eq!(node.tag_name().name(), "reading");
 
-    let jouyou = node.attribute("r_type") == Some("ja_jlpt");
-    let typ = match node.attribute("r_type") {
-        Some("pinyin") => ReadingType::Pinyin,
-        Some("korean_r") => ReadingType::KoreanRomanized,
-        Some("korean_h") => ReadingType::Hangul,
-        Some("ja_kun") => ReadingType::Kunyomi,
-        Some("ja_on") => ReadingType::Onyomi(
-            node.attribute("on_type")
-                .map(|on_type| on_type.parse().unwrap()),
-        ),
-        _ => panic!("Unexpected r_type"),
+    let Some(r_type) = ctx.attribute(node, "r_type")?
+    else {
+        return Ok(None);
+    };
+
+    let jouyou = r_type == "ja_jlpt";
+    let typ = match r_type {
+        "pinyin" => ReadingType::Pinyin,
+        "korean_r" => ReadingType::KoreanRomanized,
+        "korean_h" => ReadingType::Hangul,
+        "ja_kun" => ReadingType::Kunyomi,
+        "ja_on" => ReadingType::Onyomi(match node.attribute("on_type") {
+            Some(on_type) => ctx.parse_value(node, on_type, str::parse)?,
+            None => None,
+        }),
+        _ => {
+            ctx.invalid(node, r_type, "unknown r_type")?;
+            return Ok(None);
+        }
+    };
+    let Some(value) = ctx.text(node)?
+    else {
+        return Ok(None);
     };
-    let value = node.text().unwrap().into();
 
-    Reading { jouyou, typ, value }
+    Ok(Some(Reading {
+        jouyou,
+        typ,
+        value: value.into(),
+    }))
 }
 
-fn parse_meaning(node: roxmltree::Node) -> Meaning {
+fn parse_meaning(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Option<Meaning>, ParseError> {
     assert_eq!(node.tag_name().name(), "meaning");
 
-    let lang = node
-        .attribute("m_lang")
-        .map(|m_lang| m_lang.parse().unwrap())
-        .unwrap_or(isolang::Language::Eng);
-    let text = node.text().unwrap().into();
+    let lang = match node.attribute("m_lang") {
+        Some(m_lang) => ctx
+            .parse_value(node, m_lang, str::parse)?
+            .unwrap_or(isolang::Language::Und),
+        None => isolang::Language::Eng,
+    };
+    let Some(text) = ctx.text(node)?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(Meaning {
+        lang,
+        text: text.into(),
+    }))
+}
 
-    Meaning { lang, text }
+#[cfg(test)]
+mod test {
+    use crate::kanjidic2::{Dictionary, QueryCode, VariantType};
+    use crate::parse_error::{ParseContext, ParseMode};
+
+    #[test]
+    fn test_parse_character() {
+        let xml = r#"<kanjidic2>
+<character>
+<literal>広</literal>
+<codepoint><cp_value cp_type="ucs">5e83</cp_value></codepoint>
+<radical><rad_value rad_type="classical">53</rad_value></radical>
+<misc>
+<grade>2</grade>
+<stroke_count>5</stroke_count>
+<stroke_count>4</stroke_count>
+<variant var_type="jis208">1-54-4</variant>
+<variant var_type="ucs">5ee3</variant>
+<freq>247</freq>
+<jlpt>3</jlpt>
+</misc>
+<dic_number>
+<dic_ref dr_type="heisig">741</dic_ref>
+<dic_ref dr_type="moro" m_vol="4" m_page="0374">9281</dic_ref>
+</dic_number>
+<query_code>
+<q_code qc_type="skip">3-3-2</q_code>
+<q_code qc_type="skip" skip_misclass="posn">2-3-2</q_code>
+<q_code qc_type="sh_desc">3q2.3</q_code>
+<q_code qc_type="four_corner">0023.7</q_code>
+<q_code qc_type="deroo">1749</q_code>
+</query_code>
+</character>
+</kanjidic2>"#;
+        let document = roxmltree::Document::parse(xml).unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let (_, characters) = super::parse_kanjidic2(document.root_element(), &mut ctx).unwrap();
+        let misc = &characters[0].misc;
+
+        assert_eq!(misc.grade, Some(2));
+        assert_eq!(misc.stroke_count(), Some(5));
+        assert_eq!(misc.miscounts(), [4]);
+        assert_eq!(misc.frequency, Some(247));
+        assert_eq!(misc.jlpt, Some(3));
+        assert_eq!(misc.variants[0].typ, VariantType::Jis208);
+        assert_eq!(misc.variants[1].character(), Some('廣'));
+        assert!(misc.radical_names.is_empty());
+
+        let character = &characters[0];
+        assert_eq!(character.dictionary_index(Dictionary::Heisig), Some("741"));
+        assert_eq!(character.dic_number[1].volume, Some(4));
+        assert_eq!(character.dic_number[1].page, Some(374));
+        assert!(character.has_skip_code("3-3-2".parse().unwrap(), false));
+        assert!(!character.has_skip_code("2-3-2".parse().unwrap(), false));
+        assert!(character.has_skip_code("2-3-2".parse().unwrap(), true));
+        assert_eq!(character.query_code.len(), 5);
+        assert_eq!(character.query_code[4], QueryCode::DeRoo(1749));
+    }
+
+    #[test]
+    fn test_parse_stream() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE kanjidic2 [
+<!ELEMENT kanjidic2 (header,character*)>
+]>
+<kanjidic2>
+<header>
+<file_version>4</file_version>
+<database_version>2024-001</database_version>
+<date_of_creation>2024-01-01</date_of_creation>
+</header>
+<!-- Entry for Kanji: 広 -->
+<character>
+<literal>広</literal>
+<codepoint><cp_value cp_type="ucs">5e83</cp_value></codepoint>
+<radical><rad_value rad_type="classical">53</rad_value></radical>
+<misc><grade>2</grade><stroke_count>5</stroke_count><freq>247</freq></misc>
+<query_code><q_code qc_type="skip">3-3-2</q_code></query_code>
+<reading_meaning>
+<rmgroup>
+<reading r_type="ja_on">コウ</reading>
+<reading r_type="ja_kun">ひろ.い</reading>
+<meaning>wide</meaning>
+<meaning m_lang="fr">large</meaning>
+</rmgroup>
+<nanori>ひろ</nanori>
+</reading_meaning>
+</character>
+<character>
+<literal>一</literal>
+<codepoint><cp_value cp_type="ucs">4e00</cp_value></codepoint>
+<radical><rad_value rad_type="classical">1</rad_value></radical>
+<misc><grade>1</grade><stroke_count>1</stroke_count></misc>
+</character>
+</kanjidic2>"#;
+        let document = roxmltree::Document::parse_with_options(xml, roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let parsed = super::parse_kanjidic2(document.root_element(), &mut ctx).unwrap();
+        let streamed = super::parse_kanjidic2_stream(xml.as_bytes(), &mut ctx).unwrap();
+
+        assert_eq!(streamed.0.database_version, "2024-001");
+        assert_eq!(streamed.1.len(), 2);
+        assert_eq!(
+            postcard::to_allocvec(&parsed).unwrap(),
+            postcard::to_allocvec(&streamed).unwrap()
+        );
+    }
 }
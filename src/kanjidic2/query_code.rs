@@ -0,0 +1,171 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+// q_code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum QueryCode {
+    // qc_type="skip"
+    Skip(SkipCode),
+    // qc_type="skip" skip_misclass="..."
+    // A code people commonly arrive at instead of the correct one
+    SkipMisclassification(SkipCode, SkipMisclassification),
+    // qc_type="sh_desc"
+    SpahnHadamitzky(ShDescriptor),
+    // qc_type="four_corner"
+    FourCorner(FourCornerCode),
+    // qc_type="deroo"
+    DeRoo(u16),
+}
+
+// q_code skip_misclass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, strum::Display)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[rustfmt::skip]
+pub enum SkipMisclassification {
+    #[strum(serialize = "posn",            to_string = "position")]                 Position,
+    #[strum(serialize = "stroke_count",    to_string = "stroke count")]             StrokeCount,
+    #[strum(serialize = "stroke_and_posn", to_string = "stroke count and position")] StrokeCountAndPosition,
+    #[strum(serialize = "stroke_diff",     to_string = "ambiguous stroke count")]   StrokeDifference,
+}
+
+/// Returned when a query code doesn't have the expected format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidQueryCode;
+impl Display for InvalidQueryCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("invalid query code")
+    }
+}
+impl std::error::Error for InvalidQueryCode {}
+
+/// Code of the "System of Kanji Indexing by Patterns" from Halpern's dictionaries.
+/// The pattern is 1 (left-right), 2 (top-bottom), 3 (enclosure) or 4 (solid), followed by the stroke counts of the two parts.
+/// Solid kanji use the total stroke count and a subpattern (1: top line, 2: bottom line, 3: through line, 4: other) instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct SkipCode {
+    pub pattern: u8,
+    pub first:   u8,
+    pub second:  u8,
+}
+impl FromStr for SkipCode {
+    type Err = InvalidQueryCode;
+
+    /// ```
+    /// use jdict2::kanjidic2::SkipCode;
+    ///
+    /// let skip: SkipCode = "1-4-3".parse().unwrap();
+    /// assert_eq!((skip.pattern, skip.first, skip.second), (1, 4, 3));
+    /// assert_eq!(skip.to_string(), "1-4-3");
+    /// assert!("5-1-1".parse::<SkipCode>().is_err());
+    /// assert!("1-4".parse::<SkipCode>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split('-').map(|part| part.parse::<u8>());
+        let (Some(Ok(pattern)), Some(Ok(first)), Some(Ok(second)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(InvalidQueryCode);
+        };
+        if !(1..=4).contains(&pattern) {
+            return Err(InvalidQueryCode);
+        }
+
+        Ok(SkipCode {
+            pattern,
+            first,
+            second,
+        })
+    }
+}
+impl Display for SkipCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}-{}", self.pattern, self.first, self.second)
+    }
+}
+
+/// Descriptor from Spahn & Hadamitzky's dictionaries, e.g. "2k1.1":
+/// stroke count of the radical, the radical's letter, remaining stroke count and an index among kanji with the same prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct ShDescriptor {
+    pub radical_strokes: u8,
+    pub radical: char,
+    pub other_strokes: u8,
+    pub index: u16,
+}
+impl FromStr for ShDescriptor {
+    type Err = InvalidQueryCode;
+
+    /// ```
+    /// use jdict2::kanjidic2::ShDescriptor;
+    ///
+    /// let desc: ShDescriptor = "7b6.1".parse().unwrap();
+    /// assert_eq!(desc.radical_strokes, 7);
+    /// assert_eq!(desc.radical, 'b');
+    /// assert_eq!(desc.other_strokes, 6);
+    /// assert_eq!(desc.index, 1);
+    /// assert_eq!(desc.to_string(), "7b6.1");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let letter = s
+            .find(|c: char| c.is_ascii_alphabetic())
+            .ok_or(InvalidQueryCode)?;
+        let (other_strokes, index) = s[letter + 1..].split_once('.').ok_or(InvalidQueryCode)?;
+
+        Ok(ShDescriptor {
+            radical_strokes: s[..letter].parse().map_err(|_| InvalidQueryCode)?,
+            radical: s[letter..].chars().next().ok_or(InvalidQueryCode)?,
+            other_strokes: other_strokes.parse().map_err(|_| InvalidQueryCode)?,
+            index: index.parse().map_err(|_| InvalidQueryCode)?,
+        })
+    }
+}
+impl Display for ShDescriptor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}{}.{}",
+            self.radical_strokes, self.radical, self.other_strokes, self.index
+        )
+    }
+}
+
+/// Four-corner code, e.g. "3413.1": the shapes at the four corners of the kanji and an extra fifth corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct FourCornerCode {
+    pub corners: [u8; 4],
+    pub extra:   u8,
+}
+impl FromStr for FourCornerCode {
+    type Err = InvalidQueryCode;
+
+    /// ```
+    /// use jdict2::kanjidic2::FourCornerCode;
+    ///
+    /// let code: FourCornerCode = "3413.1".parse().unwrap();
+    /// assert_eq!(code.corners, [3, 4, 1, 3]);
+    /// assert_eq!(code.extra, 1);
+    /// assert_eq!(code.to_string(), "3413.1");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let digit = |c: char| c.to_digit(10).map(|d| d as u8).ok_or(InvalidQueryCode);
+
+        let (corners, extra) = s.split_once('.').ok_or(InvalidQueryCode)?;
+        let corners: Vec<u8> = corners.chars().map(digit).collect::<Result<_, _>>()?;
+        let mut extra = extra.chars();
+        let (Ok(corners), Some(extra), None) = (corners.try_into(), extra.next(), extra.next())
+        else {
+            return Err(InvalidQueryCode);
+        };
+
+        Ok(FourCornerCode {
+            corners,
+            extra: digit(extra)?,
+        })
+    }
+}
+impl Display for FourCornerCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [a, b, c, d] = self.corners;
+        write!(f, "{a}{b}{c}{d}.{}", self.extra)
+    }
+}
@@ -22,6 +22,81 @@ pub struct Reading {
     pub value:  CompactString,
 }
 
+impl Reading {
+    /// The parts of this reading if it is a kun'yomi.
+    pub fn kun(&self) -> Option<KunReading<'_>> {
+        match self.typ {
+            ReadingType::Kunyomi => Some(KunReading::parse(&self.value)),
+            _ => None,
+        }
+    }
+}
+
+/// A kun'yomi split into the reading of the kanji itself and the okurigana that follow it.
+/// kanjidic2 writes these as "た.べる", with a leading '-' for readings only used as a suffix
+/// and a trailing '-' for those only used as a prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KunReading<'a> {
+    pub stem: &'a str,
+    pub okurigana: &'a str,
+    // Only used in front of other words, e.g. お- for 御
+    pub prefix: bool,
+    // Only used after other words, e.g. -かた for 方
+    pub suffix: bool,
+}
+impl<'a> KunReading<'a> {
+    /// ```
+    /// use jdict2::kanjidic2::KunReading;
+    ///
+    /// let kun = KunReading::parse("た.べる");
+    /// assert_eq!(
+    ///     (kun.stem, kun.okurigana, kun.prefix, kun.suffix),
+    ///     ("た", "べる", false, false)
+    /// );
+    /// assert_eq!(kun.with_kanji('食'), "食(た)べる");
+    /// assert_eq!(kun.written('食'), "食べる");
+    /// assert_eq!(kun.reading(), "たべる");
+    ///
+    /// let kun = KunReading::parse("-かた");
+    /// assert_eq!(
+    ///     (kun.stem, kun.okurigana, kun.prefix, kun.suffix),
+    ///     ("かた", "", false, true)
+    /// );
+    /// assert_eq!(kun.with_kanji('方'), "〜方(かた)");
+    ///
+    /// assert!(KunReading::parse("お-").prefix);
+    /// ```
+    pub fn parse(value: &'a str) -> Self {
+        let suffix = value.starts_with('-');
+        let prefix = value.ends_with('-');
+        let value = value.trim_matches('-');
+        let (stem, okurigana) = value.split_once('.').unwrap_or((value, ""));
+        Self {
+            stem,
+            okurigana,
+            prefix,
+            suffix,
+        }
+    }
+
+    /// The reading without markers, e.g. "たべる".
+    pub fn reading(&self) -> String { format!("{}{}", self.stem, self.okurigana) }
+
+    /// How the word is written with the kanji, e.g. "食べる".
+    pub fn written(&self, kanji: char) -> String { format!("{kanji}{}", self.okurigana) }
+
+    /// The kanji with its reading in parentheses, followed by the okurigana and "〜" for affixes, e.g. "食(た)べる".
+    pub fn with_kanji(&self, kanji: char) -> String {
+        format!(
+            "{}{kanji}({}){}{}",
+            if self.suffix { "〜" } else { "" },
+            self.stem,
+            self.okurigana,
+            if self.prefix { "〜" } else { "" },
+        )
+    }
+}
+
 // reading r_type,on_type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -0,0 +1,81 @@
+use smallvec::smallvec;
+
+use super::{
+    Character,
+    Dictionary,
+    DictionaryReference,
+    Meaning,
+    QueryCode,
+    Reading,
+    ReadingMeaning,
+    ReadingMeaningGroup,
+    ReadingType,
+};
+
+/// A character without readings or meanings, to be filled in with the builder methods below, for tests.
+pub(crate) fn character(unicode: char) -> Character {
+    Character {
+        unicode,
+        radicals: smallvec![],
+        radicals_nelson_c: None,
+        misc: Default::default(),
+        dic_number: Vec::new(),
+        query_code: Vec::new(),
+        reading_meaning: smallvec![ReadingMeaning {
+            nanori: Vec::new(),
+            reading_meaning_groups: vec![ReadingMeaningGroup {
+                readings: Vec::new(),
+                meanings: Vec::new(),
+            }],
+        }],
+    }
+}
+
+impl Character {
+    pub(crate) fn frequency(mut self, frequency: u16) -> Self {
+        self.misc.frequency = Some(frequency);
+        self
+    }
+
+    pub(crate) fn stroke_count(mut self, stroke_count: u8) -> Self {
+        self.misc.stroke_counts.push(stroke_count);
+        self
+    }
+
+    pub(crate) fn query_code(mut self, code: QueryCode) -> Self {
+        self.query_code.push(code);
+        self
+    }
+
+    pub(crate) fn dic_ref(mut self, dictionary: Dictionary, index: &str) -> Self {
+        self.dic_number.push(DictionaryReference {
+            dictionary,
+            index: index.into(),
+            volume: None,
+            page: None,
+        });
+        self
+    }
+
+    pub(crate) fn readings(mut self, typ: ReadingType, values: &[&str]) -> Self {
+        self.reading_meaning[0].reading_meaning_groups[0]
+            .readings
+            .extend(values.iter().map(|value| Reading {
+                jouyou: false,
+                typ,
+                value: (*value).into(),
+            }));
+        self
+    }
+
+    /// English meanings.
+    pub(crate) fn meanings(mut self, texts: &[&str]) -> Self {
+        self.reading_meaning[0].reading_meaning_groups[0]
+            .meanings
+            .extend(texts.iter().map(|text| Meaning {
+                lang: isolang::Language::Eng,
+                text: (*text).into(),
+            }));
+        self
+    }
+}
@@ -0,0 +1,133 @@
+use super::{Child, Position, Radical, Stroke, StrokeGroup};
+
+/// One part of a kanji and the parts it is made of, see [`StrokeGroup::decomposition`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub element:  Option<char>,
+    // The character this component is a form of, e.g. 人 for 亻
+    pub original: Option<char>,
+    pub position: Option<Position>,
+    pub radical:  Option<Radical>,
+    // Set if this component gives the kanji its on'yomi, to the component that serves as the phonetic
+    pub phonetic: Option<char>,
+    // Indices of this component's strokes in stroke order, see StrokeGroup::strokes
+    pub strokes:  Vec<usize>,
+    pub children: Vec<Component>,
+}
+
+impl StrokeGroup {
+    /// The tree of components this group is made of.
+    /// Groups without an element are merged into their parent, and components that KanjiVG splits in parts
+    /// (like 行 around 韋 in 衛) are joined back together.
+    pub fn decomposition(&self) -> Component {
+        let mut next_stroke = 0;
+        decompose(self, &mut next_stroke)
+    }
+
+    /// All strokes of this group and its subgroups, in stroke order.
+    pub fn strokes(&self) -> Box<dyn Iterator<Item = &Stroke> + '_> {
+        Box::new(self.subgroups.iter().flat_map(|child| match child {
+            Child::Stroke(stroke) => Box::new(std::iter::once(stroke)),
+            Child::Group(group) => group.strokes(),
+        }))
+    }
+}
+
+fn decompose(group: &StrokeGroup, next_stroke: &mut usize) -> Component {
+    let mut component = Component {
+        element:  group.element,
+        original: group.original,
+        position: group.position,
+        radical:  group.radical,
+        phonetic: group.phon,
+        strokes:  Vec::new(),
+        children: Vec::new(),
+    };
+    add_children(group, &mut component, next_stroke);
+    component
+}
+
+fn add_children(group: &StrokeGroup, parent: &mut Component, next_stroke: &mut usize) {
+    for child in &group.subgroups {
+        match child {
+            Child::Stroke(_) => {
+                parent.strokes.push(*next_stroke);
+                *next_stroke += 1;
+            }
+            Child::Group(group) if group.element.is_none() => {
+                add_children(group, parent, next_stroke);
+            }
+            Child::Group(group) => {
+                let component = decompose(group, next_stroke);
+                parent.strokes.extend(&component.strokes);
+
+                let first_part = match group.part {
+                    Some(part) if part > 1 => parent
+                        .children
+                        .iter_mut()
+                        .rev()
+                        .find(|c| c.element == component.element),
+                    _ => None,
+                };
+                match first_part {
+                    Some(first_part) => {
+                        first_part.strokes.extend(component.strokes);
+                        first_part.children.extend(component.children);
+                    }
+                    None => parent.children.push(component),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::kanjivg::testing::strokes;
+    use crate::kanjivg::{Position, StrokeGroup};
+
+    #[test]
+    fn test_decomposition() {
+        // 衛: 行 split around 韋, with an unnamed group around 韋
+        let part = |part, count| StrokeGroup {
+            element: Some('行'),
+            part: Some(part),
+            subgroups: strokes(count),
+            ..Default::default()
+        };
+        let kanji = StrokeGroup {
+            element: Some('衛'),
+            subgroups: vec![
+                part(1, 3).into(),
+                StrokeGroup {
+                    subgroups: vec![StrokeGroup {
+                        element: Some('韋'),
+                        position: Some(Position::Top),
+                        subgroups: strokes(10),
+                        ..Default::default()
+                    }
+                    .into()],
+                    ..Default::default()
+                }
+                .into(),
+                part(2, 3).into(),
+            ],
+            ..Default::default()
+        };
+
+        let decomposition = kanji.decomposition();
+        assert_eq!(decomposition.strokes, (0..16).collect::<Vec<_>>());
+        assert_eq!(decomposition.children.len(), 2);
+
+        let gyou = &decomposition.children[0];
+        assert_eq!(gyou.element, Some('行'));
+        assert_eq!(gyou.strokes, [0, 1, 2, 13, 14, 15]);
+
+        let i = &decomposition.children[1];
+        assert_eq!(i.element, Some('韋'));
+        assert_eq!(i.position, Some(Position::Top));
+        assert_eq!(i.strokes, (3..13).collect::<Vec<_>>());
+
+        assert_eq!(kanji.strokes().count(), 16);
+    }
+}
@@ -0,0 +1,499 @@
+use std::fmt::{self, Write};
+
+use super::{Command, Coord, Path, StrokeGroup};
+
+/// An opaque color for [`DiagramStyle`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+impl Rgb {
+    fn hex(self) -> String { format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2) }
+}
+
+/// Layout and colors of a stroke order diagram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiagramStyle {
+    /// Width and height of each panel, in pixels (or SVG user units).
+    pub panel_size: f32,
+    /// Panels per row. Kanji with more strokes wrap onto further rows.
+    pub columns: usize,
+    /// In the units of [`Coord`], so relative to the size of the kanji.
+    pub stroke_width: f32,
+    /// None for a transparent background.
+    pub background: Option<Rgb>,
+    /// Strokes drawn in earlier panels.
+    pub ink: Rgb,
+    /// The stroke added in each panel, and the dot marking where it starts.
+    pub highlight: Rgb,
+    /// Panel borders and the dashed lines dividing each panel in quarters.
+    pub guides: Option<Rgb>,
+    /// Stroke number in the corner of each panel.
+    pub numbers: Option<Rgb>,
+}
+impl Default for DiagramStyle {
+    fn default() -> Self {
+        Self {
+            panel_size: 109.0,
+            columns: 10,
+            stroke_width: 3.0 / 109.0,
+            background: Some(Rgb(255, 255, 255)),
+            ink: Rgb(40, 40, 40),
+            highlight: Rgb(208, 32, 32),
+            guides: Some(Rgb(200, 200, 200)),
+            numbers: Some(Rgb(128, 128, 128)),
+        }
+    }
+}
+
+// Largest PNG that will be rasterized, 256 MiB of pixel data
+const MAX_PIXELS: u64 = 1 << 26;
+
+/// Returned when a stroke order diagram can't be rendered.
+#[derive(Debug)]
+pub enum DiagramError {
+    /// [`DiagramStyle::panel_size`] isn't a positive number.
+    InvalidPanelSize(f32),
+    /// The PNG would have more pixels than we are willing to rasterize.
+    TooLarge {
+        width:  u32,
+        height: u32,
+    },
+    Encoding(png::EncodingError),
+}
+impl fmt::Display for DiagramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DiagramError::InvalidPanelSize(size) => write!(f, "invalid panel size {size}"),
+            DiagramError::TooLarge { width, height } => {
+                write!(f, "{width}×{height} pixels is too large to rasterize")
+            }
+            DiagramError::Encoding(error) => write!(f, "failed to encode PNG: {error}"),
+        }
+    }
+}
+impl std::error::Error for DiagramError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DiagramError::Encoding(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+impl From<png::EncodingError> for DiagramError {
+    fn from(error: png::EncodingError) -> Self { DiagramError::Encoding(error) }
+}
+
+/// Render a stroke order diagram as an SVG document: one panel per stroke, each adding that stroke in the highlight
+/// color on top of the ones before it.
+pub fn stroke_order_svg(kanji: &StrokeGroup, style: &DiagramStyle) -> Result<String, DiagramError> {
+    let (width, height) = diagram_size(kanji, style)?;
+    let mut canvas = SvgCanvas {
+        svg: format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n"
+        ),
+    };
+    draw_diagram(&mut canvas, kanji, style, (width as f32, height as f32));
+    canvas.svg.push_str("</svg>\n");
+    Ok(canvas.svg)
+}
+
+/// Render a stroke order diagram like [`stroke_order_svg`], rasterized and encoded as PNG.
+/// Since there is no font to rasterize, stroke numbers are drawn as seven-segment digits.
+pub fn stroke_order_png(
+    kanji: &StrokeGroup,
+    style: &DiagramStyle,
+) -> Result<Vec<u8>, DiagramError> {
+    let (width, height) = diagram_size(kanji, style)?;
+    if width as u64 * height as u64 > MAX_PIXELS {
+        return Err(DiagramError::TooLarge { width, height });
+    }
+    // diagram_size rules out empty pixmaps and the limit overflowing ones, this is just in case
+    let pixmap =
+        tiny_skia::Pixmap::new(width, height).ok_or(DiagramError::TooLarge { width, height })?;
+    let mut canvas = PixmapCanvas(pixmap);
+    draw_diagram(&mut canvas, kanji, style, (width as f32, height as f32));
+
+    let pixels: Vec<u8> = canvas
+        .0
+        .pixels()
+        .iter()
+        .flat_map(|pixel| {
+            let color = pixel.demultiply();
+            [color.red(), color.green(), color.blue(), color.alpha()]
+        })
+        .collect();
+
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+    Ok(png)
+}
+
+// Whole pixels, with at least one panel so that kanji without strokes still give a valid image
+fn diagram_size(kanji: &StrokeGroup, style: &DiagramStyle) -> Result<(u32, u32), DiagramError> {
+    if !(style.panel_size.is_finite() && style.panel_size > 0.0) {
+        return Err(DiagramError::InvalidPanelSize(style.panel_size));
+    }
+    let panels = kanji.stroke_count().max(1);
+    let columns = style.columns.clamp(1, panels);
+    let rows = panels.div_ceil(columns);
+    // Saturates rather than wrapping, so oversized diagrams fail in stroke_order_png
+    let size = |count: usize| ((count as f64 * style.panel_size as f64).ceil() as u32).max(1);
+    Ok((size(columns), size(rows)))
+}
+
+// The drawing operations shared by the SVG and PNG output, in pixels unless noted otherwise
+trait Canvas {
+    fn fill(&mut self, size: (f32, f32), color: Rgb);
+    fn line(&mut self, from: Coord, to: Coord, width: f32, color: Rgb, dashed: bool);
+    /// `path` is in the units of [`Coord`], placed at `origin` and scaled by `scale`. So is the width.
+    fn path(&mut self, path: &Path, origin: Coord, scale: f32, width: f32, color: Rgb);
+    fn dot(&mut self, center: Coord, radius: f32, color: Rgb);
+    /// `height` is the height of the digits, `pos` their top left corner.
+    fn number(&mut self, pos: Coord, height: f32, number: usize, color: Rgb);
+}
+
+fn draw_diagram(
+    canvas: &mut impl Canvas,
+    kanji: &StrokeGroup,
+    style: &DiagramStyle,
+    size: (f32, f32),
+) {
+    if let Some(background) = style.background {
+        canvas.fill(size, background);
+    }
+
+    let strokes: Vec<&Path> = kanji.strokes().map(|stroke| &stroke.path).collect();
+    let columns = style.columns.clamp(1, strokes.len().max(1));
+    // Same margin as the stroke player in the app
+    let margin = style.panel_size / 20.0;
+    let scale = style.panel_size - 2.0 * margin;
+    let guide_width = (style.panel_size / 100.0).max(1.0);
+
+    for (idx, new_stroke) in strokes.iter().enumerate() {
+        let corner = Coord {
+            x: (idx % columns) as f32 * style.panel_size,
+            y: (idx / columns) as f32 * style.panel_size,
+        };
+        let at = |x: f32, y: f32| Coord {
+            x: corner.x + x * style.panel_size,
+            y: corner.y + y * style.panel_size,
+        };
+
+        if let Some(guides) = style.guides {
+            // Borders are shared with the neighbouring panels, so each panel draws its right and bottom edge
+            canvas.line(at(1.0, 0.0), at(1.0, 1.0), guide_width, guides, false);
+            canvas.line(at(0.0, 1.0), at(1.0, 1.0), guide_width, guides, false);
+            if idx % columns == 0 {
+                canvas.line(at(0.0, 0.0), at(0.0, 1.0), guide_width, guides, false);
+            }
+            if idx < columns {
+                canvas.line(at(0.0, 0.0), at(1.0, 0.0), guide_width, guides, false);
+            }
+            canvas.line(at(0.5, 0.0), at(0.5, 1.0), guide_width, guides, true);
+            canvas.line(at(0.0, 0.5), at(1.0, 0.5), guide_width, guides, true);
+        }
+
+        let origin = Coord {
+            x: corner.x + margin,
+            y: corner.y + margin,
+        };
+        for previous in &strokes[..idx] {
+            canvas.path(previous, origin, scale, style.stroke_width, style.ink);
+        }
+        canvas.path(
+            new_stroke,
+            origin,
+            scale,
+            style.stroke_width,
+            style.highlight,
+        );
+        if let Some(Command::MoveTo(start)) = new_stroke.0.first() {
+            canvas.dot(
+                Coord {
+                    x: origin.x + start.x * scale,
+                    y: origin.y + start.y * scale,
+                },
+                style.stroke_width * scale,
+                style.highlight,
+            );
+        }
+
+        if let Some(numbers) = style.numbers {
+            canvas.number(at(0.04, 0.04), style.panel_size / 8.0, idx + 1, numbers);
+        }
+    }
+}
+
+struct SvgCanvas {
+    svg: String,
+}
+impl Canvas for SvgCanvas {
+    fn fill(&mut self, (width, height): (f32, f32), color: Rgb) {
+        let _ = writeln!(
+            self.svg,
+            "<rect width=\"{width}\" height=\"{height}\" fill=\"{}\"/>",
+            color.hex()
+        );
+    }
+
+    fn line(&mut self, from: Coord, to: Coord, width: f32, color: Rgb, dashed: bool) {
+        let dash = match dashed {
+            true => format!(" stroke-dasharray=\"{0} {0}\"", width * 3.0),
+            false => String::new(),
+        };
+        let _ = writeln!(
+            self.svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{width}\"{dash}/>",
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            color.hex()
+        );
+    }
+
+    fn path(&mut self, path: &Path, origin: Coord, scale: f32, width: f32, color: Rgb) {
+        let mut d = String::new();
+        for command in &path.0 {
+            let _ = match command {
+                Command::MoveTo(c) => write!(d, "M{:.4},{:.4}", c.x, c.y),
+                Command::LineTo(c) => write!(d, "L{:.4},{:.4}", c.x, c.y),
+                Command::CubicBezier(c1, c2, to) => write!(
+                    d,
+                    "C{:.4},{:.4} {:.4},{:.4} {:.4},{:.4}",
+                    c1.x, c1.y, c2.x, c2.y, to.x, to.y
+                ),
+                Command::QuadBezier(c, to) => {
+                    write!(d, "Q{:.4},{:.4} {:.4},{:.4}", c.x, c.y, to.x, to.y)
+                }
+            };
+        }
+        let _ = writeln!(
+            self.svg,
+            "<path d=\"{d}\" transform=\"translate({} {}) scale({scale})\" fill=\"none\" stroke=\"{}\" \
+             stroke-width=\"{width}\" stroke-linecap=\"round\" stroke-linejoin=\"round\"/>",
+            origin.x,
+            origin.y,
+            color.hex()
+        );
+    }
+
+    fn dot(&mut self, center: Coord, radius: f32, color: Rgb) {
+        let _ = writeln!(
+            self.svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{radius}\" fill=\"{}\"/>",
+            center.x,
+            center.y,
+            color.hex()
+        );
+    }
+
+    fn number(&mut self, pos: Coord, height: f32, number: usize, color: Rgb) {
+        // Digits are about 0.7em high, the text is positioned by its baseline
+        let _ = writeln!(
+            self.svg,
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{}\" fill=\"{}\">{number}</text>",
+            pos.x,
+            pos.y + height,
+            height / 0.7,
+            color.hex()
+        );
+    }
+}
+
+struct PixmapCanvas(tiny_skia::Pixmap);
+impl PixmapCanvas {
+    fn paint(color: Rgb) -> tiny_skia::Paint<'static> {
+        let mut paint = tiny_skia::Paint::default();
+        paint.set_color_rgba8(color.0, color.1, color.2, 255);
+        paint.anti_alias = true;
+        paint
+    }
+
+    fn stroke(width: f32) -> tiny_skia::Stroke {
+        tiny_skia::Stroke {
+            width,
+            line_cap: tiny_skia::LineCap::Round,
+            line_join: tiny_skia::LineJoin::Round,
+            ..Default::default()
+        }
+    }
+}
+impl Canvas for PixmapCanvas {
+    fn fill(&mut self, _size: (f32, f32), color: Rgb) {
+        self.0
+            .fill(tiny_skia::Color::from_rgba8(color.0, color.1, color.2, 255));
+    }
+
+    fn line(&mut self, from: Coord, to: Coord, width: f32, color: Rgb, dashed: bool) {
+        let mut path = tiny_skia::PathBuilder::new();
+        path.move_to(from.x, from.y);
+        path.line_to(to.x, to.y);
+        let Some(path) = path.finish()
+        else {
+            return;
+        };
+        let mut stroke = Self::stroke(width);
+        stroke.line_cap = tiny_skia::LineCap::Butt;
+        if dashed {
+            stroke.dash = tiny_skia::StrokeDash::new(vec![width * 3.0, width * 3.0], 0.0);
+        }
+        self.0.stroke_path(
+            &path,
+            &Self::paint(color),
+            &stroke,
+            tiny_skia::Transform::identity(),
+            None,
+        );
+    }
+
+    fn path(&mut self, path: &Path, origin: Coord, scale: f32, width: f32, color: Rgb) {
+        let mut builder = tiny_skia::PathBuilder::new();
+        for command in &path.0 {
+            match *command {
+                Command::MoveTo(c) => builder.move_to(c.x, c.y),
+                Command::LineTo(c) => builder.line_to(c.x, c.y),
+                Command::CubicBezier(c1, c2, to) => {
+                    builder.cubic_to(c1.x, c1.y, c2.x, c2.y, to.x, to.y)
+                }
+                Command::QuadBezier(c, to) => builder.quad_to(c.x, c.y, to.x, to.y),
+            }
+        }
+        let Some(path) = builder.finish()
+        else {
+            return;
+        };
+        self.0.stroke_path(
+            &path,
+            &Self::paint(color),
+            &Self::stroke(width),
+            tiny_skia::Transform::from_row(scale, 0.0, 0.0, scale, origin.x, origin.y),
+            None,
+        );
+    }
+
+    fn dot(&mut self, center: Coord, radius: f32, color: Rgb) {
+        let Some(circle) = tiny_skia::PathBuilder::from_circle(center.x, center.y, radius)
+        else {
+            return;
+        };
+        self.0.fill_path(
+            &circle,
+            &Self::paint(color),
+            tiny_skia::FillRule::Winding,
+            tiny_skia::Transform::identity(),
+            None,
+        );
+    }
+
+    fn number(&mut self, pos: Coord, height: f32, number: usize, color: Rgb) {
+        let width = height * 0.5;
+        let pen = (height / 8.0).max(1.0);
+        // Leave room for the round caps, so the digits are as high as they are in the SVG
+        let (height, top) = (height - pen, pos.y + pen / 2.0);
+        let mut left = pos.x + pen / 2.0;
+        for digit in number.to_string().bytes().map(|b| b - b'0') {
+            let mut builder = tiny_skia::PathBuilder::new();
+            for (segment, ((x1, y1), (x2, y2))) in SEGMENTS.iter().enumerate() {
+                if DIGITS[digit as usize] & (1 << segment) != 0 {
+                    builder.move_to(left + x1 * width, top + y1 * height);
+                    builder.line_to(left + x2 * width, top + y2 * height);
+                }
+            }
+            if let Some(path) = builder.finish() {
+                self.0.stroke_path(
+                    &path,
+                    &Self::paint(color),
+                    &Self::stroke(pen),
+                    tiny_skia::Transform::identity(),
+                    None,
+                );
+            }
+            left += width + pen * 2.0;
+        }
+    }
+}
+
+// Seven-segment digits: top, top right, bottom right, bottom, bottom left, top left and middle segment
+type Segment = ((f32, f32), (f32, f32));
+const SEGMENTS: [Segment; 7] = [
+    ((0.0, 0.0), (1.0, 0.0)),
+    ((1.0, 0.0), (1.0, 0.5)),
+    ((1.0, 0.5), (1.0, 1.0)),
+    ((0.0, 1.0), (1.0, 1.0)),
+    ((0.0, 0.5), (0.0, 1.0)),
+    ((0.0, 0.0), (0.0, 0.5)),
+    ((0.0, 0.5), (1.0, 0.5)),
+];
+// The segments lit for each digit, as bits in the order of SEGMENTS
+const DIGITS: [u8; 10] = [
+    0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+    0b1111111, 0b1101111,
+];
+
+#[cfg(test)]
+mod test {
+    use super::{stroke_order_png, stroke_order_svg, DiagramError, DiagramStyle};
+    use crate::kanjivg::testing::kanji;
+    use crate::kanjivg::StrokeGroup;
+
+    #[test]
+    fn test_stroke_order_diagram() {
+        let kanji = kanji('三', &[
+            "M25,22c10,1,50,-1,60,-2",
+            "M30,54c8,1,40,0,48,-1",
+            "M14,88c15,1,70,0,82,-2",
+        ]);
+        let style = DiagramStyle {
+            panel_size: 100.0,
+            columns: 2,
+            ..Default::default()
+        };
+
+        let svg = stroke_order_svg(&kanji, &style).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("width=\"200\" height=\"200\""));
+        // 1 + 2 + 3 strokes over the three panels, one of them highlighted in each
+        assert_eq!(svg.matches("<path ").count(), 6);
+        assert_eq!(svg.matches("stroke=\"#d02020\"").count(), 3);
+        assert!(svg.contains(">3</text>"));
+
+        let png = stroke_order_png(&kanji, &style).unwrap();
+        let decoder = png::Decoder::new(std::io::Cursor::new(png));
+        let info = decoder.read_info().unwrap().info().clone();
+        assert_eq!((info.width, info.height), (200, 200));
+
+        // No strokes still makes a single empty panel
+        let empty = stroke_order_svg(&StrokeGroup::default(), &style).unwrap();
+        assert!(empty.contains("width=\"100\" height=\"100\""));
+        assert!(stroke_order_png(&StrokeGroup::default(), &style).is_ok());
+
+        for panel_size in [f32::INFINITY, f32::NAN, 0.0, -1.0] {
+            let style = DiagramStyle {
+                panel_size,
+                ..Default::default()
+            };
+            assert!(matches!(
+                stroke_order_svg(&kanji, &style),
+                Err(DiagramError::InvalidPanelSize(_))
+            ));
+            assert!(matches!(
+                stroke_order_png(&kanji, &style),
+                Err(DiagramError::InvalidPanelSize(_))
+            ));
+        }
+        let huge = DiagramStyle {
+            panel_size: 1e8,
+            ..Default::default()
+        };
+        assert!(matches!(
+            stroke_order_png(&kanji, &huge),
+            Err(DiagramError::TooLarge { .. })
+        ));
+    }
+}
@@ -1,7 +1,18 @@
+pub mod component;
+pub mod diagram;
 pub mod path;
+pub mod recognition;
+#[cfg(test)] pub(crate) mod testing;
+use std::io::BufRead;
+
 use roxmltree::NodeType;
 
+pub use self::component::*;
+pub use self::diagram::*;
 pub use self::path::*;
+pub use self::recognition::*;
+use crate::parse_error::{ParseContext, ParseError, ParseErrorKind};
+use crate::xml_stream::XmlStream;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, serde::Serialize, serde::Deserialize)]
 #[rustfmt::skip]
@@ -12,7 +23,7 @@ pub enum Radical {
     #[strum(serialize = "jis")]     Jis,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::EnumString, strum::Display, strum::EnumIter, serde::Serialize, serde::Deserialize)]
 #[rustfmt::skip]
 pub enum Position {
     #[strum(serialize = "top")]    Top,
@@ -42,6 +53,33 @@ pub struct StrokeGroup {
     pub element:   Option<char>,
     pub subgroups: Vec<Child>,
 }
+impl StrokeGroup {
+    /// Number of strokes in this group and all its subgroups.
+    pub fn stroke_count(&self) -> usize {
+        self.subgroups
+            .iter()
+            .map(|child| match child {
+                Child::Stroke(_) => 1,
+                Child::Group(group) => group.stroke_count(),
+            })
+            .sum()
+    }
+
+    /// This group and all groups nested in it, depth first.
+    pub fn groups(&self) -> Box<dyn Iterator<Item = &StrokeGroup> + '_> {
+        Box::new(
+            std::iter::once(self).chain(
+                self.subgroups
+                    .iter()
+                    .filter_map(|child| match child {
+                        Child::Group(group) => Some(group),
+                        Child::Stroke(_) => None,
+                    })
+                    .flat_map(|group| group.groups()),
+            ),
+        )
+    }
+}
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum Child {
     Stroke(Stroke),
@@ -60,105 +98,142 @@ pub struct Stroke {
     pub typ:  Option<char>,
 }
 
-pub fn parse_kanjivg(root: roxmltree::Node) -> Vec<StrokeGroup> {
-    assert_eq!(root.tag_name().name(), "kanjivg");
+pub fn parse_kanjivg(
+    root: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Vec<StrokeGroup>, ParseError> {
+    if root.tag_name().name() != "kanjivg" {
+        return Err(ctx.error(
+            root,
+            ParseErrorKind::UnexpectedElement(root.tag_name().name().into()),
+        ));
+    }
 
     let mut kanji_strokes = Vec::new();
     for node in root.children() {
         match (node.node_type(), node.tag_name().name()) {
-            (NodeType::Element, "kanji") => {
-                let id = node.attribute("id").unwrap();
-                let unicode =
-                    char::from_u32(u32::from_str_radix(&id[id.len() - 5..], 16).unwrap()).unwrap();
-
-                let mut value = None;
-                for node in node.children() {
-                    match (node.node_type(), node.tag_name().name()) {
-                        (NodeType::Element, "g") => {
-                            assert!(value.is_none());
-                            value = Some(parse_group(node));
-                        }
-                        (NodeType::Text, _) => (),
-                        (ty, name) => panic!("Unexpected child in <kanji>: {:?} {}", ty, name),
-                    }
-                }
-                let mut value = value.unwrap();
-                value.element = Some(unicode);
-                kanji_strokes.push(value);
+            (NodeType::Element, "kanji") => kanji_strokes.extend(parse_kanji(node, ctx)?),
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
+        }
+    }
+    Ok(kanji_strokes)
+}
+
+/// Like [`parse_kanjivg`], but reads the document one `<kanji>` at a time instead of loading it as a whole.
+pub fn parse_kanjivg_stream(
+    reader: impl BufRead,
+    ctx: &mut ParseContext,
+) -> Result<Vec<StrokeGroup>, ParseError> {
+    let mut stream = XmlStream::new(reader)?;
+    stream.expect_root("kanjivg")?;
+
+    let mut kanji_strokes = Vec::new();
+    stream.for_each_element(ctx, |node, ctx| {
+        match node.tag_name().name() {
+            "kanji" => kanji_strokes.extend(parse_kanji(node, ctx)?),
+            _ => ctx.unexpected(node)?,
+        }
+        Ok(())
+    })?;
+    Ok(kanji_strokes)
+}
+
+fn parse_kanji(
+    node: roxmltree::Node,
+    ctx: &mut ParseContext,
+) -> Result<Option<StrokeGroup>, ParseError> {
+    assert_eq!(node.tag_name().name(), "kanji");
+
+    let Some(id) = ctx.attribute(node, "id")?
+    else {
+        return Ok(None);
+    };
+    // e.g. kvg:kanji_04e00
+    let Some(unicode) = ctx.parse_value(node, id, |id| {
+        let hex = id.rsplit('_').next().unwrap_or(id);
+        u32::from_str_radix(hex, 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or("not a codepoint")
+    })?
+    else {
+        return Ok(None);
+    };
+
+    let mut value = None;
+    for node in node.children() {
+        match (node.node_type(), node.tag_name().name()) {
+            (NodeType::Element, "g") if value.is_none() => {
+                value = Some(parse_group(node, ctx)?);
             }
-            (NodeType::Text, _) => (),
-            (ty, name) => panic!("Unexpected child in <kanjivg>: {:?} {}", ty, name),
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
         }
     }
-    kanji_strokes
+    let Some(mut value) = value
+    else {
+        let error = ctx.error(node, ParseErrorKind::MissingChild("g"));
+        ctx.recover(error)?;
+        return Ok(None);
+    };
+    value.element = Some(unicode);
+    Ok(Some(value))
 }
 
-fn parse_group(node: roxmltree::Node) -> StrokeGroup {
+fn parse_group(node: roxmltree::Node, ctx: &mut ParseContext) -> Result<StrokeGroup, ParseError> {
     assert_eq!(node.tag_name().name(), "g");
 
     let mut group = StrokeGroup::default();
 
     for attrib in node.attributes() {
+        let value = attrib.value();
         match attrib.name() {
             "id" => (),
-            "element" => group.element = attrib.value().chars().next(),
-            "variant" => match attrib.value() {
-                "true" => group.variant = true,
-                "false" => group.variant = false,
-                _ => panic!("Unexpected value for variant: {}", attrib.value()),
-            },
-            "original" => group.original = attrib.value().chars().next(),
-            "radical" => {
-                group.radical = Some(attrib.value().parse().unwrap_or_else(|e| {
-                    panic!("Unexpected value for radical: {} ({})", attrib.value(), e)
-                }))
-            }
-            "position" => {
-                group.position = Some(attrib.value().parse().unwrap_or_else(|e| {
-                    panic!("Unexpected value for position: {} ({})", attrib.value(), e)
-                }))
+            "element" => group.element = value.chars().next(),
+            "variant" => {
+                if let Some(variant) = ctx.parse_value(node, value, str::parse)? {
+                    group.variant = variant;
+                }
             }
-            "part" => group.part = Some(attrib.value().parse().unwrap()),
-            "number" => group.number = Some(attrib.value().parse().unwrap()),
+            "original" => group.original = value.chars().next(),
+            "radical" => group.radical = ctx.parse_value(node, value, str::parse)?,
+            "position" => group.position = ctx.parse_value(node, value, str::parse)?,
+            "part" => group.part = ctx.parse_value(node, value, str::parse)?,
+            "number" => group.number = ctx.parse_value(node, value, str::parse)?,
             "partial" => {
-                group.partial = match attrib.value() {
-                    "true" => true,
-                    "false" => false,
-                    _ => panic!("Unexpected value for partial: {}", attrib.value()),
+                if let Some(partial) = ctx.parse_value(node, value, str::parse)? {
+                    group.partial = partial;
                 }
             }
-            "phon" => group.phon = attrib.value().chars().next(),
+            "phon" => group.phon = value.chars().next(),
             "radicalForm" => {
-                group.radical_form = match attrib.value() {
-                    "true" => true,
-                    "false" => false,
-                    _ => panic!("Unexpected value for radicalForm: {}", attrib.value()),
+                if let Some(radical_form) = ctx.parse_value(node, value, str::parse)? {
+                    group.radical_form = radical_form;
                 }
             }
             "tradForm" => {
-                group.trad_form = match attrib.value() {
-                    "true" => true,
-                    "false" => false,
-                    _ => panic!("Unexpected value for tradForm: {}", attrib.value()),
+                if let Some(trad_form) = ctx.parse_value(node, value, str::parse)? {
+                    group.trad_form = trad_form;
                 }
             }
-            _ => panic!("Unexpected attribute in <g>: {}", attrib.name()),
+            _ => ctx.unexpected_attribute(node, &attrib)?,
         }
     }
 
     for node in node.children() {
         match (node.node_type(), node.tag_name().name()) {
-            (NodeType::Element, "path") => group.subgroups.push(parse_path(node).into()),
-            (NodeType::Element, "g") => group.subgroups.push(parse_group(node).into()),
-            (NodeType::Text, _) => (),
-            (ty, name) => panic!("Unexpected child in <g>: {:?} {}", ty, name),
+            (NodeType::Element, "path") => group.subgroups.push(parse_path(node, ctx)?.into()),
+            (NodeType::Element, "g") => group.subgroups.push(parse_group(node, ctx)?.into()),
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
         }
     }
 
-    group
+    Ok(group)
 }
 
-fn parse_path(node: roxmltree::Node) -> Stroke {
+fn parse_path(node: roxmltree::Node, ctx: &mut ParseContext) -> Result<Stroke, ParseError> {
     assert_eq!(node.tag_name().name(), "path");
 
     let mut stroke = Stroke {
@@ -171,18 +246,68 @@ fn parse_path(node: roxmltree::Node) -> Stroke {
             "id" => (),
             "type" => stroke.typ = attrib.value().chars().next(),
             "d" => {
-                stroke.path =
-                    Path::parse_from_svg_path_data(attrib.value(), (0.0, 0.0, 109.0, 109.0))
+                stroke.path = ctx
+                    .parse_value(node, attrib.value(), |d| {
+                        Path::parse_from_svg_path_data(d, (0.0, 0.0, 109.0, 109.0))
+                    })?
+                    .unwrap_or_default()
             }
-            _ => panic!("Unexpected attribute in <path>: {}", attrib.name()),
+            _ => ctx.unexpected_attribute(node, &attrib)?,
         }
     }
     for node in node.children() {
         match (node.node_type(), node.tag_name().name()) {
-            (NodeType::Text, _) => (),
-            (ty, name) => panic!("Unexpected child in <path>: {:?} {}", ty, name),
+            (NodeType::Text | NodeType::Comment, _) => (),
+            _ => ctx.unexpected(node)?,
         }
     }
 
-    stroke
+    Ok(stroke)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::parse_error::{ParseContext, ParseMode};
+
+    #[test]
+    fn test_parse_stream() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE kanjivg [
+<!ATTLIST g
+xmlns:kvg CDATA #FIXED "http://kanjivg.tagaini.net"
+kvg:element CDATA #IMPLIED>
+]>
+<kanjivg xmlns:kvg='http://kanjivg.tagaini.net'>
+<!-- 一 -->
+<kanji id="kvg:kanji_04e00">
+<g id="kvg:04e00" kvg:element="一" kvg:radical="general">
+<path id="kvg:04e00-s1" kvg:type="㇐" d="M11,54.25c3.19,0.62,6.25,0.75,9.73,0.5c20.64-1.5,50.39-5.12,68.58-5.24"/>
+</g>
+</kanji>
+<kanji id="kvg:kanji_053e3">
+<g id="kvg:053e3" kvg:element="口" kvg:radical="general">
+<path id="kvg:053e3-s1" kvg:type="㇑" d="M21.25,30.5c1.25,1.25,2.23,2.73,2.5,4.5c1.5,9.75,3.25,26.5,4.5,38.5"/>
+<path id="kvg:053e3-s2" kvg:type="㇕b" d="M23.5,33c17.5-2.5,46.25-5.25,59.25-5.75c3.68-0.14,5.5,2.25,5,5.5c-1.5,9.75-4.25,24.5-6.5,36.5"/>
+<path id="kvg:053e3-s3" kvg:type="㇐b" d="M28.75,70.25c12.25-1.25,37.5-3.25,53.25-3.75"/>
+</g>
+</kanji>
+</kanjivg>"#;
+        let document = roxmltree::Document::parse_with_options(xml, roxmltree::ParsingOptions {
+            allow_dtd: true,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mut ctx = ParseContext::new(ParseMode::Strict);
+        let kanji = super::parse_kanjivg(document.root_element(), &mut ctx).unwrap();
+        let streamed = super::parse_kanjivg_stream(xml.as_bytes(), &mut ctx).unwrap();
+
+        assert_eq!(streamed.len(), 2);
+        assert_eq!(streamed[1].element, Some('口'));
+        assert_eq!(streamed[1].subgroups.len(), 3);
+        assert_eq!(
+            postcard::to_allocvec(&kanji).unwrap(),
+            postcard::to_allocvec(&streamed).unwrap()
+        );
+    }
 }
@@ -1,307 +1,656 @@
-use std::str::FromStr;
+use std::fmt;
 
-#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+/// Maximum distance between a curve and the polyline approximating it when measuring lengths, in the units of
+/// [`Coord`] (so 1/1000th of the kanji's size).
+const LENGTH_TOLERANCE: f32 = 0.001;
+// Depth limit for subdividing curves, in case the tolerance can't be reached due to rounding
+const MAX_SUBDIVISIONS: u32 = 16;
+
+/// A single drawing command with absolute coordinates.
+/// Shorthands from the SVG path data (H, V, S, T, A, Z) are resolved to these while parsing.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum Command {
     MoveTo(Coord),
     LineTo(Coord),
+    // First control point, second control point, end point
     CubicBezier(Coord, Coord, Coord),
-    CubicSpline(Coord, Coord),
+    // Control point, end point
     QuadBezier(Coord, Coord),
-    QuadSpline(Coord),
 }
 
-#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Coord {
     pub x: f32,
     pub y: f32,
 }
 impl Coord {
-    fn distance(&self, other: &Coord) -> f32 {
+    pub fn distance(&self, other: &Coord) -> f32 {
         let dx = self.x - other.x;
         let dy = self.y - other.y;
         (dx * dx + dy * dy).sqrt()
     }
+
+    fn lerp(&self, other: &Coord, t: f32) -> Coord {
+        Coord {
+            x: self.x + (other.x - self.x) * t,
+            y: self.y + (other.y - self.y) * t,
+        }
+    }
+}
+impl From<(f32, f32)> for Coord {
+    fn from((x, y): (f32, f32)) -> Self { Coord { x, y } }
 }
 
-#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+/// Returned when a path data string doesn't follow the SVG path grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathError {
+    // Byte offset into the path data
+    pub offset: usize,
+    pub reason: &'static str,
+}
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.reason, self.offset)
+    }
+}
+impl std::error::Error for PathError {}
+
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Path(pub Vec<Command>);
 impl Path {
-    /// Parse an SVG path data string into a Path.
+    /// Parse an SVG path data string into a Path, with coordinates relative to the view box (0..1).
     /// Example:
     /// ```
-    /// use jdict2::kanjivg::path::Path;
+    /// use jdict2::kanjivg::path::{Command, Coord, Path};
     /// let path = Path::parse_from_svg_path_data(
     ///     "M99.22,15.85c-4.09,15.34-17.51,64.94-20.89,77.06",
     ///     (0.0, 0.0, 200.0, 200.0),
-    /// );
+    /// )
+    /// .unwrap();
+    /// assert_eq!(path.0.len(), 2);
+    ///
+    /// // Shorthands are resolved to lines and Béziers
+    /// let path = Path::parse_from_svg_path_data("M0 0h10v10z", (0.0, 0.0, 10.0, 10.0)).unwrap();
+    /// assert_eq!(path.0[2], Command::LineTo(Coord { x: 1.0, y: 1.0 }));
+    /// assert_eq!(path.0[3], Command::LineTo(Coord { x: 0.0, y: 0.0 }));
+    ///
+    /// assert!(Path::parse_from_svg_path_data("M0 0L5", (0.0, 0.0, 10.0, 10.0)).is_err());
     /// ```
-    pub fn parse_from_svg_path_data(d: &str, view_box: (f32, f32, f32, f32)) -> Self {
-        // println!("d: {}", d);
-
+    pub fn parse_from_svg_path_data(
+        d: &str,
+        view_box: (f32, f32, f32, f32),
+    ) -> Result<Self, PathError> {
         let mut path_builder = PathBuilder {
             path: Path::default(),
-            _last_control: (0.0, 0.0),
             pos: (0.0, 0.0),
-            viewbox: view_box,
+            subpath_start: (0.0, 0.0),
+            last_control: None,
         };
 
-        let mut d = d;
-        while !d.is_empty() {
-            d = path_builder.parse_action(d);
-        }
-
-        path_builder.path
-    }
-
-    pub fn length(&self) -> f32 {
-        struct TapeMeasure {
-            length: f32,
-            brush_position: Coord,
-        }
-        impl TapeMeasure {
-            fn move_to(&mut self, to: &Coord) { self.brush_position = *to; }
-            fn line_to(&mut self, to: &Coord) {
-                self.length += self.brush_position.distance(to);
-                self.brush_position = *to;
+        let mut parser = Parser { d, offset: 0 };
+        let mut previous = None;
+        loop {
+            parser.skip_separators();
+            let Some(next) = parser.peek()
+            else {
+                break;
+            };
+
+            let cmd = if next.is_ascii_alphabetic() {
+                parser.offset += 1;
+                next
             }
+            else {
+                // Parameters without a command letter repeat the previous command, where a moveto repeats as lineto
+                match previous {
+                    Some(b'M') => b'L',
+                    Some(b'm') => b'l',
+                    Some(b'Z' | b'z') | None => return Err(parser.error("expected a command")),
+                    Some(cmd) => cmd,
+                }
+            };
+            path_builder.parse_action(cmd, &mut parser)?;
+            previous = Some(cmd);
         }
 
-        let mut tape_measure = TapeMeasure {
-            length: 0.0,
-            brush_position: Coord::default(),
+        let (minx, miny, width, height) = view_box;
+        let normalize = |c: &mut Coord| {
+            c.x = (c.x - minx) / width;
+            c.y = (c.y - miny) / height;
         };
-
-        for cmd in self.0.iter() {
+        for cmd in &mut path_builder.path.0 {
             match cmd {
-                Command::MoveTo(to) => tape_measure.move_to(to),
-                Command::LineTo(to) => tape_measure.line_to(to),
+                Command::MoveTo(to) | Command::LineTo(to) => normalize(to),
                 Command::CubicBezier(c1, c2, to) => {
-                    tape_measure.line_to(c1);
-                    tape_measure.line_to(c2);
-                    tape_measure.line_to(to);
-                }
-                Command::CubicSpline(c2, to) => {
-                    tape_measure.line_to(c2);
-                    tape_measure.line_to(to);
+                    normalize(c1);
+                    normalize(c2);
+                    normalize(to);
                 }
                 Command::QuadBezier(c1, to) => {
-                    tape_measure.line_to(c1);
-                    tape_measure.line_to(to);
+                    normalize(c1);
+                    normalize(to);
                 }
-                Command::QuadSpline(to) => {
-                    tape_measure.line_to(to);
+            }
+        }
+
+        Ok(path_builder.path)
+    }
+
+    /// The curve approximated by straight lines, with one polyline for each MoveTo.
+    /// No point of the curve is further than `tolerance` from the polyline.
+    pub fn polylines(&self, tolerance: f32) -> Vec<Vec<Coord>> {
+        let mut polylines: Vec<Vec<Coord>> = Vec::new();
+        let mut pos = Coord::default();
+        for cmd in &self.0 {
+            if !matches!(cmd, Command::MoveTo(_)) && polylines.is_empty() {
+                polylines.push(vec![pos]);
+            }
+            match (*cmd, as_cubic(pos, cmd)) {
+                (Command::MoveTo(to), _) => polylines.push(vec![to]),
+                (_, Some(cubic)) => {
+                    let polyline = polylines.last_mut().unwrap();
+                    subdivide_cubic(cubic, tolerance, MAX_SUBDIVISIONS, &mut |piece| {
+                        polyline.push(piece[3])
+                    });
                 }
+                (_, None) => polylines.last_mut().unwrap().push(end_point(cmd)),
             }
+            pos = end_point(cmd);
         }
+        polylines
+    }
 
-        tape_measure.length
+    /// Length of the curve, not counting the jumps between subpaths.
+    /// ```
+    /// use jdict2::kanjivg::path::Path;
+    /// let path =
+    ///     Path::parse_from_svg_path_data("M0,5 A5,5 0 0 1 10,5", (0.0, 0.0, 10.0, 10.0)).unwrap();
+    /// assert!((path.length() - std::f32::consts::FRAC_PI_2).abs() < 0.001);
+    /// ```
+    pub fn length(&self) -> f32 {
+        let mut length = 0.0;
+        let mut pos = Coord::default();
+        for cmd in &self.0 {
+            match (*cmd, as_cubic(pos, cmd)) {
+                (Command::MoveTo(_), _) => (),
+                (_, Some(cubic)) => {
+                    subdivide_cubic(cubic, LENGTH_TOLERANCE, MAX_SUBDIVISIONS, &mut |piece| {
+                        // Gravesen's estimate, much closer than the chord alone
+                        let [p0, p1, p2, p3] = piece;
+                        let polygon = p0.distance(&p1) + p1.distance(&p2) + p2.distance(&p3);
+                        length += (2.0 * p0.distance(&p3) + polygon) / 3.0;
+                    });
+                }
+                (_, None) => length += pos.distance(&end_point(cmd)),
+            }
+            pos = end_point(cmd);
+        }
+        length
+    }
+}
+
+// Control points of a Bézier starting at `pos`, where quadratic ones are raised to cubic ones
+fn as_cubic(pos: Coord, cmd: &Command) -> Option<[Coord; 4]> {
+    match *cmd {
+        Command::MoveTo(_) | Command::LineTo(_) => None,
+        Command::CubicBezier(c1, c2, to) => Some([pos, c1, c2, to]),
+        Command::QuadBezier(c, to) => {
+            Some([pos, pos.lerp(&c, 2.0 / 3.0), to.lerp(&c, 2.0 / 3.0), to])
+        }
     }
 }
 
+fn end_point(cmd: &Command) -> Coord {
+    match *cmd {
+        Command::MoveTo(to)
+        | Command::LineTo(to)
+        | Command::CubicBezier(_, _, to)
+        | Command::QuadBezier(_, to) => to,
+    }
+}
+
+/// Split a cubic Bézier in halves until each piece is within `tolerance` of its chord, and call `flat` with the pieces in order.
+fn subdivide_cubic(
+    points: [Coord; 4],
+    tolerance: f32,
+    depth: u32,
+    flat: &mut impl FnMut([Coord; 4]),
+) {
+    let [p0, p1, p2, p3] = points;
+    // The curve lies within the control polygon, so if that is barely longer than the chord,
+    // no point of the curve can be far from the chord
+    let polygon = p0.distance(&p1) + p1.distance(&p2) + p2.distance(&p3);
+    let chord = p0.distance(&p3);
+    if depth == 0 || polygon - chord <= tolerance {
+        flat(points);
+        return;
+    }
+
+    // de Casteljau
+    let p01 = p0.lerp(&p1, 0.5);
+    let p12 = p1.lerp(&p2, 0.5);
+    let p23 = p2.lerp(&p3, 0.5);
+    let p012 = p01.lerp(&p12, 0.5);
+    let p123 = p12.lerp(&p23, 0.5);
+    let mid = p012.lerp(&p123, 0.5);
+    subdivide_cubic([p0, p01, p012, mid], tolerance, depth - 1, flat);
+    subdivide_cubic([mid, p123, p23, p3], tolerance, depth - 1, flat);
+}
+
+// Builds commands in the coordinate system of the path data, they are normalized once parsing is done
 struct PathBuilder {
     path: Path,
-    _last_control: (f32, f32),
     pos: (f32, f32),
-    viewbox: (f32, f32, f32, f32),
+    subpath_start: (f32, f32),
+    // Last control point of the previous command if it was a Bézier, for reflecting in S and T
+    last_control: Option<LastControl>,
+}
+#[derive(Clone, Copy)]
+enum LastControl {
+    Cubic((f32, f32)),
+    Quad((f32, f32)),
 }
 impl PathBuilder {
-    fn move_to_internal(&mut self, relative: bool, coord: (f32, f32)) -> Coord {
-        self.pos = if !relative {
-            coord
+    fn absolute(&self, relative: bool, coord: (f32, f32)) -> (f32, f32) {
+        match relative {
+            true => (self.pos.0 + coord.0, self.pos.1 + coord.1),
+            false => coord,
         }
-        else {
-            (self.pos.0 + coord.0, self.pos.1 + coord.1)
-        };
+    }
 
-        let (minx, miny, width, height) = self.viewbox;
-        Coord {
-            x: ((self.pos.0 - minx) / width),
-            y: ((self.pos.1 - miny) / height),
+    // The previous control point mirrored at the current position, or the current position if there is none
+    fn reflected(&self, cubic: bool) -> (f32, f32) {
+        match (self.last_control, cubic) {
+            (Some(LastControl::Cubic(c)), true) | (Some(LastControl::Quad(c)), false) => {
+                (2.0 * self.pos.0 - c.0, 2.0 * self.pos.1 - c.1)
+            }
+            _ => self.pos,
         }
     }
-    fn get_coord_internal(&self, relative: bool, coord: (f32, f32)) -> Coord {
-        let position = if !relative {
-            coord
+
+    fn move_to(&mut self, to: (f32, f32)) {
+        self.path.0.push(Command::MoveTo(to.into()));
+        self.pos = to;
+        self.subpath_start = to;
+    }
+
+    fn line_to(&mut self, to: (f32, f32)) {
+        self.path.0.push(Command::LineTo(to.into()));
+        self.pos = to;
+    }
+
+    fn cubic_bezier(&mut self, c1: (f32, f32), c2: (f32, f32), to: (f32, f32)) {
+        self.path
+            .0
+            .push(Command::CubicBezier(c1.into(), c2.into(), to.into()));
+        self.pos = to;
+    }
+
+    fn quad_bezier(&mut self, c: (f32, f32), to: (f32, f32)) {
+        self.path.0.push(Command::QuadBezier(c.into(), to.into()));
+        self.pos = to;
+    }
+
+    // A, converted to cubic Béziers following the SVG implementation notes (B.2.4 and B.2.5)
+    fn arc(
+        &mut self,
+        radii: (f32, f32),
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: (f32, f32),
+    ) {
+        let from = self.pos;
+        if from == to {
+            return;
         }
-        else {
-            (self.pos.0 + coord.0, self.pos.1 + coord.1)
+        let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+        if rx == 0.0 || ry == 0.0 {
+            self.line_to(to);
+            return;
+        }
+
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        // Midpoint between the end points, in the ellipse's coordinate system
+        let dx = (from.0 - to.0) / 2.0;
+        let dy = (from.1 - to.1) / 2.0;
+        let x1 = cos * dx + sin * dy;
+        let y1 = -sin * dx + cos * dy;
+
+        // Scale up radii that are too small to reach the end point
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut factor = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            factor = -factor;
+        }
+        let cx1 = factor * rx * y1 / ry;
+        let cy1 = -factor * ry * x1 / rx;
+
+        let center = (
+            cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0,
+            sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0,
+        );
+
+        let angle =
+            |ux: f32, uy: f32, vx: f32, vy: f32| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+        let start_angle = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+        let mut sweep_angle = angle(
+            (x1 - cx1) / rx,
+            (y1 - cy1) / ry,
+            (-x1 - cx1) / rx,
+            (-y1 - cy1) / ry,
+        );
+        if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= std::f32::consts::TAU;
+        }
+        else if sweep && sweep_angle < 0.0 {
+            sweep_angle += std::f32::consts::TAU;
+        }
+
+        // Each Bézier covers at most a quarter of the ellipse
+        let segments = (sweep_angle.abs() / std::f32::consts::FRAC_PI_2)
+            .ceil()
+            .max(1.0);
+        let delta = sweep_angle / segments;
+        let handle = 4.0 / 3.0 * (delta / 4.0).tan();
+        let point = |theta: f32| {
+            let (s, c) = theta.sin_cos();
+            (
+                center.0 + cos * rx * c - sin * ry * s,
+                center.1 + sin * rx * c + cos * ry * s,
+            )
+        };
+        let derivative = |theta: f32| {
+            let (s, c) = theta.sin_cos();
+            (-cos * rx * s - sin * ry * c, -sin * rx * s + cos * ry * c)
         };
 
-        let (minx, miny, width, height) = self.viewbox;
-        Coord {
-            x: ((position.0 - minx) / width),
-            y: ((position.1 - miny) / height),
+        let mut theta = start_angle;
+        for segment in 0..segments as u32 {
+            let next = theta + delta;
+            let (p0, d0) = (point(theta), derivative(theta));
+            let d1 = derivative(next);
+            // Land exactly on the end point, regardless of rounding
+            let p1 = match segment + 1 == segments as u32 {
+                true => to,
+                false => point(next),
+            };
+            self.cubic_bezier(
+                (p0.0 + handle * d0.0, p0.1 + handle * d0.1),
+                (p1.0 - handle * d1.0, p1.1 - handle * d1.1),
+                p1,
+            );
+            theta = next;
         }
     }
 
-    fn move_to(&mut self, relative: bool, to: (f32, f32)) {
-        let action = Command::MoveTo(self.move_to_internal(relative, to));
-        self.path.0.push(action);
-    }
+    fn parse_action(&mut self, cmd: u8, parser: &mut Parser) -> Result<(), PathError> {
+        let relative = cmd.is_ascii_lowercase();
+        let mut last_control = None;
 
-    fn line_to(&mut self, relative: bool, to: (f32, f32)) {
-        let action = Command::LineTo(self.move_to_internal(relative, to));
-        self.path.0.push(action);
-    }
+        match cmd.to_ascii_uppercase() {
+            b'M' => {
+                let to = self.absolute(relative, parser.coord()?);
+                self.move_to(to);
+            }
+            b'Z' => {
+                self.line_to(self.subpath_start);
+            }
+            b'L' => {
+                let to = self.absolute(relative, parser.coord()?);
+                self.line_to(to);
+            }
+            b'H' => {
+                let x = parser.number()?;
+                let x = match relative {
+                    true => self.pos.0 + x,
+                    false => x,
+                };
+                self.line_to((x, self.pos.1));
+            }
+            b'V' => {
+                let y = parser.number()?;
+                let y = match relative {
+                    true => self.pos.1 + y,
+                    false => y,
+                };
+                self.line_to((self.pos.0, y));
+            }
+            b'C' => {
+                let c1 = self.absolute(relative, parser.coord()?);
+                let c2 = self.absolute(relative, parser.coord()?);
+                let to = self.absolute(relative, parser.coord()?);
+                self.cubic_bezier(c1, c2, to);
+                last_control = Some(LastControl::Cubic(c2));
+            }
+            b'S' => {
+                let c1 = self.reflected(true);
+                let c2 = self.absolute(relative, parser.coord()?);
+                let to = self.absolute(relative, parser.coord()?);
+                self.cubic_bezier(c1, c2, to);
+                last_control = Some(LastControl::Cubic(c2));
+            }
+            b'Q' => {
+                let c = self.absolute(relative, parser.coord()?);
+                let to = self.absolute(relative, parser.coord()?);
+                self.quad_bezier(c, to);
+                last_control = Some(LastControl::Quad(c));
+            }
+            b'T' => {
+                let c = self.reflected(false);
+                let to = self.absolute(relative, parser.coord()?);
+                self.quad_bezier(c, to);
+                last_control = Some(LastControl::Quad(c));
+            }
+            b'A' => {
+                let radii = parser.coord()?;
+                let rotation = parser.number()?;
+                let large_arc = parser.flag()?;
+                let sweep = parser.flag()?;
+                let to = self.absolute(relative, parser.coord()?);
+                self.arc(radii, rotation, large_arc, sweep, to);
+            }
+            _ => {
+                parser.offset -= 1;
+                return Err(parser.error("unknown command"));
+            }
+        }
 
-    // C
-    fn cubic_bezier(&mut self, relative: bool, c1: (f32, f32), c2: (f32, f32), to: (f32, f32)) {
-        let c1 = self.get_coord_internal(relative, c1);
-        let c2 = self.get_coord_internal(relative, c2);
-        let to = self.move_to_internal(relative, to);
-        self.path.0.push(Command::CubicBezier(c1, c2, to));
+        self.last_control = last_control;
+        Ok(())
     }
+}
 
-    // S
-    fn cubic_spline(&mut self, relative: bool, c2: (f32, f32), to: (f32, f32)) {
-        let c2 = self.get_coord_internal(relative, c2);
-        let to = self.move_to_internal(relative, to);
-        self.path.0.push(Command::CubicSpline(c2, to));
+struct Parser<'a> {
+    d: &'a str,
+    offset: usize,
+}
+impl Parser<'_> {
+    fn error(&self, reason: &'static str) -> PathError {
+        PathError {
+            offset: self.offset,
+            reason,
+        }
     }
 
-    // Q
-    fn quad_bezier(&mut self, relative: bool, c1: (f32, f32), to: (f32, f32)) {
-        let c1 = self.get_coord_internal(relative, c1);
-        let to = self.move_to_internal(relative, to);
-        self.path.0.push(Command::QuadBezier(c1, to));
+    fn peek(&self) -> Option<u8> { self.d.as_bytes().get(self.offset).copied() }
+
+    fn skip_separators(&mut self) {
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_whitespace() || c == b',')
+        {
+            self.offset += 1;
+        }
     }
 
-    // T
-    fn quad_spline(&mut self, relative: bool, to: (f32, f32)) {
-        let to = self.move_to_internal(relative, to);
-        self.path.0.push(Command::QuadSpline(to));
+    fn skip_digits(&mut self) -> usize {
+        let start = self.offset;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.offset += 1;
+        }
+        self.offset - start
     }
 
-    fn parse_action<'a>(&mut self, mut s: &'a str) -> &'a str {
-        // println!("s: {}", s);
-        let cmd;
-        (s, cmd) = snip(s, |c| c.is_ascii_alphabetic());
-        (s, _) = snip_whitespace_or_comma(s);
-        assert_eq!(
-            cmd.len(),
-            1,
-            "Unexpected path action: '{}' followed by '{}'",
-            cmd,
-            s
-        );
-        let cmd = cmd.as_bytes()[0];
-        let relative = cmd.is_ascii_lowercase();
+    // Numbers can follow each other without separators if that's unambiguous, e.g. "1-2" or "0.5.5"
+    fn number(&mut self) -> Result<f32, PathError> {
+        self.skip_separators();
+        let start = self.offset;
 
-        match cmd {
-            b'M' | b'm' => loop {
-                let c;
-                (s, c) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                self.move_to(relative, c);
+        if matches!(self.peek(), Some(b'-' | b'+')) {
+            self.offset += 1;
+        }
+        let mut digits = self.skip_digits();
+        if self.peek() == Some(b'.') {
+            self.offset += 1;
+            digits += self.skip_digits();
+        }
+        if digits == 0 {
+            self.offset = start;
+            return Err(self.error("expected a number"));
+        }
 
-                if !s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
-                {
-                    break;
-                }
-            },
-            b'L' | b'l' => loop {
-                let c;
-                (s, c) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                self.line_to(relative, c);
-
-                if !s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
-                {
-                    break;
-                }
-            },
-            b'C' | b'c' => loop {
-                let c1;
-                let c2;
-                let to;
-
-                (s, c1) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                (s, c2) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                (s, to) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                self.cubic_bezier(relative, c1, c2, to);
-
-                if !s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
-                {
-                    break;
-                }
-            },
-            b'S' | b's' => loop {
-                let c1;
-                let to;
-
-                (s, c1) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                (s, to) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                self.cubic_spline(relative, c1, to);
-
-                if !s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
-                {
-                    break;
-                }
-            },
-            b'Q' | b'q' => loop {
-                let c1;
-                let to;
-                (s, c1) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                (s, to) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                self.quad_bezier(relative, c1, to);
-
-                if !s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
-                {
-                    break;
-                }
-            },
-            b'T' | b't' => loop {
-                let c2;
-                (s, c2) = snip_xy(s);
-                (s, _) = snip_whitespace_or_comma(s);
-                self.quad_spline(relative, c2);
-
-                if !s.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
-                {
-                    break;
-                }
-            },
-            _ => panic!(
-                "Unexpected path action: {} {}",
-                char::from_u32(cmd as u32).unwrap(),
-                s
-            ),
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mantissa_end = self.offset;
+            self.offset += 1;
+            if matches!(self.peek(), Some(b'-' | b'+')) {
+                self.offset += 1;
+            }
+            if self.skip_digits() == 0 {
+                self.offset = mantissa_end;
+            }
         }
 
-        s
+        self.d[start..self.offset].parse().map_err(|_| PathError {
+            offset: start,
+            reason: "invalid number",
+        })
     }
-}
 
-fn snip(s: &str, p: impl Fn(char) -> bool) -> (&str, &str) {
-    let end = s.find(|c| !p(c)).unwrap_or(s.len());
-    let result = &s[..end];
-    let remainder = &s[end..];
-    (remainder, result)
-}
-fn snip_sign(s: &str) -> (&str, f32) {
-    let (s, sign) = snip(s, |c| c == '-' || c == '+');
-    let sign = if sign.contains('-') { -1.0 } else { 1.0 };
-    (s, sign)
-}
-fn snip_number(s: &str) -> (&str, f32) {
-    let (s, sign) = snip_sign(s);
-    let (s, num) = snip(s, |c| c.is_ascii_digit() || c == '.');
-    (s, sign * f32::from_str(num).unwrap())
-}
-fn snip_whitespace_or_comma(s: &str) -> (&str, &str) {
-    snip(s, |c| char::is_ascii_whitespace(&c) || c == ',')
+    fn coord(&mut self) -> Result<(f32, f32), PathError> { Ok((self.number()?, self.number()?)) }
+
+    // Arc flags are a single digit, and may be written without separators, e.g. "a1,1 0 00.5,.5"
+    fn flag(&mut self) -> Result<bool, PathError> {
+        self.skip_separators();
+        let flag = match self.peek() {
+            Some(b'0') => false,
+            Some(b'1') => true,
+            _ => return Err(self.error("expected a flag")),
+        };
+        self.offset += 1;
+        Ok(flag)
+    }
 }
-fn snip_xy(s: &str) -> (&str, (f32, f32)) {
-    let (s, x) = snip_number(s);
-    let (s, _) = snip(s, |c| c.is_whitespace() || c == ',');
-    let (s, y) = snip_number(s);
 
-    (s, (x, y))
+#[cfg(test)]
+mod test {
+    use super::{Command, Coord, Path};
+
+    fn parse(d: &str) -> Vec<Command> {
+        Path::parse_from_svg_path_data(d, (0.0, 0.0, 1.0, 1.0))
+            .unwrap()
+            .0
+    }
+
+    fn c(x: f32, y: f32) -> Coord { Coord { x, y } }
+
+    // Within 0.1%
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() <= 1e-3 * b.abs().max(1.0), "{a} != {b}");
+    }
+
+    #[test]
+    fn test_implicit_repeats() {
+        assert_eq!(parse("M1,2 3,4 5,6"), [
+            Command::MoveTo(c(1.0, 2.0)),
+            Command::LineTo(c(3.0, 4.0)),
+            Command::LineTo(c(5.0, 6.0)),
+        ]);
+        assert_eq!(parse("m1 1 1 1h1 1v-1-1"), [
+            Command::MoveTo(c(1.0, 1.0)),
+            Command::LineTo(c(2.0, 2.0)),
+            Command::LineTo(c(3.0, 2.0)),
+            Command::LineTo(c(4.0, 2.0)),
+            Command::LineTo(c(4.0, 1.0)),
+            Command::LineTo(c(4.0, 0.0)),
+        ]);
+        // Numbers without separators, and exponents
+        assert_eq!(parse("M.5.5L1e1-2E-1"), [
+            Command::MoveTo(c(0.5, 0.5)),
+            Command::LineTo(c(10.0, -0.2)),
+        ]);
+    }
+
+    #[test]
+    fn test_smooth_curves() {
+        assert_eq!(parse("M0,0 C0,1 1,1 1,0 S2,-1 2,0"), [
+            Command::MoveTo(c(0.0, 0.0)),
+            Command::CubicBezier(c(0.0, 1.0), c(1.0, 1.0), c(1.0, 0.0)),
+            Command::CubicBezier(c(1.0, -1.0), c(2.0, -1.0), c(2.0, 0.0)),
+        ]);
+        // Relative, and without a previous cubic to reflect
+        assert_eq!(parse("M1,1 s1,1 2,0"), [
+            Command::MoveTo(c(1.0, 1.0)),
+            Command::CubicBezier(c(1.0, 1.0), c(2.0, 2.0), c(3.0, 1.0)),
+        ]);
+        assert_eq!(parse("M0,0 Q1,1 2,0 T4,0 t2,0"), [
+            Command::MoveTo(c(0.0, 0.0)),
+            Command::QuadBezier(c(1.0, 1.0), c(2.0, 0.0)),
+            Command::QuadBezier(c(3.0, -1.0), c(4.0, 0.0)),
+            Command::QuadBezier(c(5.0, 1.0), c(6.0, 0.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_arc() {
+        // Half circle with radius 1, drawn clockwise through (1, -1)
+        let path =
+            Path::parse_from_svg_path_data("M0,0 A1,1 0 0 1 2,0", (0.0, 0.0, 1.0, 1.0)).unwrap();
+        assert_eq!(path.0.len(), 3);
+        assert_close(path.length(), std::f32::consts::PI);
+        let Command::CubicBezier(_, _, mid) = path.0[1]
+        else {
+            panic!("expected a Bézier, got {:?}", path.0[1]);
+        };
+        assert_close(mid.x, 1.0);
+        assert_close(mid.y, -1.0);
+
+        // The large arc flag picks the long way around, radii that are too small are scaled up
+        let path =
+            Path::parse_from_svg_path_data("M0,0 a1,1 0 1 0 1,1", (0.0, 0.0, 1.0, 1.0)).unwrap();
+        assert_close(path.length(), 1.5 * std::f32::consts::PI);
+        let path =
+            Path::parse_from_svg_path_data("M0,0 A.1,.1 0 0 1 2,0", (0.0, 0.0, 1.0, 1.0)).unwrap();
+        assert_close(path.length(), std::f32::consts::PI);
+    }
+
+    #[test]
+    fn test_length() {
+        let path =
+            Path::parse_from_svg_path_data("M0,0 L3,4 M10,10 l0,1", (0.0, 0.0, 1.0, 1.0)).unwrap();
+        assert_close(path.length(), 6.0);
+
+        // Quarter circle, whose control polygon is about 1.74 long
+        let path =
+            Path::parse_from_svg_path_data("M1,0 C1,0.5523 0.5523,1 0,1", (0.0, 0.0, 1.0, 1.0))
+                .unwrap();
+        assert_close(path.length(), std::f32::consts::FRAC_PI_2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let parse = |d| Path::parse_from_svg_path_data(d, (0.0, 0.0, 1.0, 1.0));
+        assert_eq!(parse("M0,0 X1").unwrap_err().offset, 5);
+        assert_eq!(parse("M0,0 L1,").unwrap_err().offset, 8);
+        assert!(parse("1,1").is_err());
+        assert!(parse("M0,0 A1,1 0 2 0 1,1").is_err());
+        assert!(parse("").unwrap().0.is_empty());
+    }
 }
@@ -0,0 +1,305 @@
+use super::{Coord, Path, StrokeGroup};
+
+/// Number of points a stroke is resampled to for comparison.
+pub const SAMPLES: usize = 10;
+// How precisely curves are followed when resampling, in units of the kanji's size
+const TOLERANCE: f32 = 0.005;
+// Added to the distance of a kanji for every stroke more or less than was drawn
+const STROKE_COUNT_PENALTY: f32 = 0.25;
+// Kanji with more or fewer strokes than this are not considered at all
+const MAX_STROKE_COUNT_DIFFERENCE: usize = 2;
+
+/// A stroke reduced to evenly spaced points along its length, in the same coordinates as [`Path`]
+/// (the 109×109 KanjiVG viewbox scaled to 0..1). This captures position, shape and direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeShape(pub [Coord; SAMPLES]);
+impl StrokeShape {
+    /// Resample a drawn stroke. Returns None if there are no points.
+    pub fn from_points(points: &[Coord]) -> Option<Self> {
+        let first = *points.first()?;
+        let length: f32 = points.windows(2).map(|s| s[0].distance(&s[1])).sum();
+
+        let mut samples = [first; SAMPLES];
+        let mut segments = points.windows(2).peekable();
+        // Length of the stroke up to the start of the current segment
+        let mut walked = 0.0;
+        for (idx, sample) in samples.iter_mut().enumerate().skip(1) {
+            let target = length * idx as f32 / (SAMPLES - 1) as f32;
+            while let Some(segment) = segments.peek() {
+                let segment_length = segment[0].distance(&segment[1]);
+                if walked + segment_length >= target && segment_length > 0.0 {
+                    let t = ((target - walked) / segment_length).clamp(0.0, 1.0);
+                    *sample = Coord {
+                        x: segment[0].x + (segment[1].x - segment[0].x) * t,
+                        y: segment[0].y + (segment[1].y - segment[0].y) * t,
+                    };
+                    break;
+                }
+                walked += segment_length;
+                segments.next();
+            }
+            if segments.peek().is_none() {
+                *sample = *points.last().unwrap();
+            }
+        }
+        Some(Self(samples))
+    }
+
+    /// Resample a KanjiVG stroke. Returns None if the path is empty.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let points: Vec<Coord> = path.polylines(TOLERANCE).into_iter().flatten().collect();
+        Self::from_points(&points)
+    }
+
+    /// Mean distance between corresponding points, so strokes drawn in the opposite direction are far apart.
+    pub fn distance(&self, other: &StrokeShape) -> f32 {
+        self.0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| a.distance(b))
+            .sum::<f32>()
+            / SAMPLES as f32
+    }
+
+    /// The same stroke, drawn from the other end.
+    pub fn reversed(&self) -> StrokeShape {
+        let mut samples = self.0;
+        samples.reverse();
+        StrokeShape(samples)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub kanji:    char,
+    // Lower is more similar, 0 for a perfect match
+    pub distance: f32,
+}
+
+/// Recognizes handwritten kanji by comparing each drawn stroke with the stroke at the same position in the
+/// stroke order of every known kanji, so stroke count, order, direction and position all matter.
+pub struct Recognizer {
+    templates: Vec<(char, Vec<StrokeShape>)>,
+}
+impl Recognizer {
+    pub fn new<'a>(kanji: impl IntoIterator<Item = (char, &'a StrokeGroup)>) -> Self {
+        let templates = kanji
+            .into_iter()
+            .map(|(c, strokes)| {
+                let shapes = strokes
+                    .strokes()
+                    .filter_map(|stroke| StrokeShape::from_path(&stroke.path))
+                    .collect();
+                (c, shapes)
+            })
+            .collect();
+        Self { templates }
+    }
+
+    /// Up to `limit` kanji that look most like `drawn`, best match first.
+    /// Each drawn stroke is a list of points in the order they were drawn, in 0..1 coordinates.
+    pub fn recognize(&self, drawn: &[Vec<Coord>], limit: usize) -> Vec<Candidate> {
+        let drawn: Vec<StrokeShape> = drawn
+            .iter()
+            .filter_map(|points| StrokeShape::from_points(points))
+            .collect();
+        if drawn.is_empty() {
+            return Vec::new();
+        }
+
+        let mut candidates: Vec<Candidate> = self
+            .templates
+            .iter()
+            .filter(|(_, shapes)| shapes.len().abs_diff(drawn.len()) <= MAX_STROKE_COUNT_DIFFERENCE)
+            .map(|(kanji, shapes)| Candidate {
+                kanji:    *kanji,
+                distance: kanji_distance(&drawn, shapes),
+            })
+            .collect();
+        candidates.sort_unstable_by(|a, b| {
+            a.distance
+                .total_cmp(&b.distance)
+                .then(a.kanji.cmp(&b.kanji))
+        });
+        candidates.truncate(limit);
+        candidates
+    }
+}
+
+/// How a stroke traced in practice compares to the one that should have been drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrokeFeedback {
+    Correct,
+    // Right shape and place, but drawn from the wrong end
+    Reversed,
+    // Matches a later stroke, given by its index
+    WrongOrder(usize),
+    // Doesn't look like any of the remaining strokes
+    Mismatch,
+}
+
+/// Checks strokes traced one by one against the stroke order of a kanji.
+pub struct StrokeChecker {
+    shapes: Vec<StrokeShape>,
+}
+impl StrokeChecker {
+    // Mean distance up to which a traced stroke counts as the same stroke, about 1/10th of the kanji's size
+    const MAX_DISTANCE: f32 = 0.1;
+
+    pub fn new(kanji: &StrokeGroup) -> Self {
+        let shapes = kanji
+            .strokes()
+            .map(|stroke| {
+                StrokeShape::from_path(&stroke.path)
+                    .unwrap_or(StrokeShape([Coord::default(); SAMPLES]))
+            })
+            .collect();
+        Self { shapes }
+    }
+
+    pub fn stroke_count(&self) -> usize { self.shapes.len() }
+
+    /// Compare `drawn` against stroke number `expected` (counting from 0).
+    pub fn check(&self, expected: usize, drawn: &[Coord]) -> StrokeFeedback {
+        let Some(drawn) = StrokeShape::from_points(drawn)
+        else {
+            return StrokeFeedback::Mismatch;
+        };
+        let matches = |shape: &StrokeShape| drawn.distance(shape) <= Self::MAX_DISTANCE;
+        let matches_reversed =
+            |shape: &StrokeShape| drawn.reversed().distance(shape) <= Self::MAX_DISTANCE;
+
+        let Some(expected_shape) = self.shapes.get(expected)
+        else {
+            return StrokeFeedback::Mismatch;
+        };
+        if matches(expected_shape) {
+            return StrokeFeedback::Correct;
+        }
+        if matches_reversed(expected_shape) {
+            return StrokeFeedback::Reversed;
+        }
+
+        // The closest of the remaining strokes, if any is close enough
+        self.shapes
+            .iter()
+            .enumerate()
+            .skip(expected + 1)
+            .filter(|(_, shape)| matches(shape) || matches_reversed(shape))
+            .min_by(|(_, a), (_, b)| {
+                let distance = |shape: &StrokeShape| {
+                    drawn.distance(shape).min(drawn.reversed().distance(shape))
+                };
+                distance(a).total_cmp(&distance(b))
+            })
+            .map_or(StrokeFeedback::Mismatch, |(idx, _)| {
+                StrokeFeedback::WrongOrder(idx)
+            })
+    }
+}
+
+fn kanji_distance(drawn: &[StrokeShape], template: &[StrokeShape]) -> f32 {
+    let matched: f32 = drawn.iter().zip(template).map(|(a, b)| a.distance(b)).sum();
+    let unmatched = drawn.len().abs_diff(template.len()) as f32 * STROKE_COUNT_PENALTY;
+    (matched + unmatched) / drawn.len().max(template.len()) as f32
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Recognizer, StrokeChecker, StrokeFeedback, StrokeShape};
+    use crate::kanjivg::testing::kanji;
+    use crate::kanjivg::Coord;
+
+    // A stroke drawn with the mouse, in KanjiVG coordinates
+    fn drawn(points: &[(f32, f32)]) -> Vec<Coord> {
+        points
+            .iter()
+            .map(|(x, y)| Coord {
+                x: x / 109.0,
+                y: y / 109.0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stroke_shape() {
+        let shape =
+            StrokeShape::from_points(&drawn(&[(0.0, 0.0), (9.0, 0.0), (109.0, 0.0)])).unwrap();
+        assert_eq!(shape.0[0], Coord { x: 0.0, y: 0.0 });
+        assert!((shape.0[3].x - 1.0 / 3.0).abs() < 1e-5);
+        assert_eq!(shape.0[9], Coord { x: 1.0, y: 0.0 });
+
+        assert_eq!(shape.distance(&shape), 0.0);
+        assert!(shape.distance(&shape.reversed()) > 0.4);
+
+        // A single click
+        let dot = StrokeShape::from_points(&drawn(&[(50.0, 50.0)])).unwrap();
+        assert!(dot.0.iter().all(|c| *c == dot.0[0]));
+        assert!(StrokeShape::from_points(&[]).is_none());
+    }
+
+    #[test]
+    fn test_recognize() {
+        let kanji = [
+            kanji('一', &["M11,54 C40,53 70,52 98,53"]),
+            kanji('二', &[
+                "M24,30 C44,29 66,28 84,29",
+                "M12,82 C40,81 70,80 97,81",
+            ]),
+            kanji('十', &[
+                "M12,50 C40,49 70,48 97,49",
+                "M54,12 C55,40 55,70 54,98",
+            ]),
+            kanji('丁', &["M14,20 L95,20", "M54,20 C56,50 56,80 46,97"]),
+        ];
+        let recognizer = Recognizer::new(kanji.iter().map(|group| (group.element.unwrap(), group)));
+        let best = |strokes: &[Vec<Coord>]| recognizer.recognize(strokes, 3)[0].kanji;
+
+        let horizontal = drawn(&[(15.0, 52.0), (50.0, 50.0), (95.0, 50.0)]);
+        let vertical = drawn(&[(52.0, 15.0), (54.0, 60.0), (53.0, 95.0)]);
+        assert_eq!(best(std::slice::from_ref(&horizontal)), '一');
+        assert_eq!(best(&[horizontal.clone(), vertical.clone()]), '十');
+        assert_eq!(
+            best(&[
+                drawn(&[(25.0, 31.0), (83.0, 30.0)]),
+                drawn(&[(10.0, 80.0), (98.0, 82.0)]),
+            ]),
+            '二'
+        );
+        // The same strokes at the top of the square look more like 丁
+        assert_eq!(
+            best(&[
+                drawn(&[(15.0, 22.0), (95.0, 21.0)]),
+                drawn(&[(54.0, 22.0), (50.0, 95.0)])
+            ]),
+            '丁'
+        );
+
+        assert_eq!(recognizer.recognize(&[horizontal], 10).len(), 4);
+        assert!(recognizer.recognize(&[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_stroke_checker() {
+        let ju = kanji('十', &[
+            "M12,50 C40,49 70,48 97,49",
+            "M54,12 C55,40 55,70 54,98",
+        ]);
+        let checker = StrokeChecker::new(&ju);
+        assert_eq!(checker.stroke_count(), 2);
+
+        let horizontal = drawn(&[(14.0, 52.0), (50.0, 50.0), (95.0, 50.0)]);
+        let vertical = drawn(&[(52.0, 15.0), (54.0, 60.0), (53.0, 95.0)]);
+        let backwards = drawn(&[(95.0, 50.0), (50.0, 50.0), (14.0, 52.0)]);
+        let scribble = drawn(&[(10.0, 10.0), (20.0, 90.0), (30.0, 10.0)]);
+
+        assert_eq!(checker.check(0, &horizontal), StrokeFeedback::Correct);
+        assert_eq!(checker.check(0, &backwards), StrokeFeedback::Reversed);
+        assert_eq!(checker.check(0, &vertical), StrokeFeedback::WrongOrder(1));
+        assert_eq!(checker.check(0, &scribble), StrokeFeedback::Mismatch);
+        assert_eq!(checker.check(1, &vertical), StrokeFeedback::Correct);
+        // Strokes that were already drawn don't count
+        assert_eq!(checker.check(1, &horizontal), StrokeFeedback::Mismatch);
+        assert_eq!(checker.check(2, &horizontal), StrokeFeedback::Mismatch);
+    }
+}
@@ -0,0 +1,30 @@
+use super::{Child, Path, Stroke, StrokeGroup};
+
+/// Strokes without a path, for tests that only count them.
+pub(crate) fn strokes(count: usize) -> Vec<Child> {
+    (0..count)
+        .map(|_| {
+            Child::Stroke(Stroke {
+                path: Path::default(),
+                typ:  None,
+            })
+        })
+        .collect()
+}
+
+/// A kanji with one stroke per SVG path data string, in the 109×109 KanjiVG viewbox.
+pub(crate) fn kanji(element: char, paths: &[&str]) -> StrokeGroup {
+    StrokeGroup {
+        element: Some(element),
+        subgroups: paths
+            .iter()
+            .map(|d| {
+                Child::Stroke(Stroke {
+                    path: Path::parse_from_svg_path_data(d, (0.0, 0.0, 109.0, 109.0)).unwrap(),
+                    typ:  None,
+                })
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
@@ -1,7 +1,14 @@
 pub mod jmdict;
+pub mod jmnedict;
 pub mod kana;
 pub mod kanjidic2;
 pub mod kanjivg;
 
+pub mod component_index;
 pub mod database;
+pub mod deinflection;
 pub mod dictionary_search;
+pub mod parse_error;
+pub mod phonetic_series;
+pub mod search_index;
+pub mod xml_stream;
@@ -0,0 +1,216 @@
+use std::fmt::Display;
+
+use roxmltree::{Node, NodeType, TextPos};
+
+/// An unexpected element, attribute or value in one of the XML sources.
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    /// Element path from the root, e.g. `JMdict/entry/sense/misc`
+    pub path: String,
+    /// Position of the element in the source text
+    pub pos:  TextPos,
+    pub kind: ParseErrorKind,
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}: {}", self.path, self.pos, self.kind)
+    }
+}
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone)]
+pub enum ParseErrorKind {
+    UnexpectedElement(String),
+    UnexpectedNode(NodeType),
+    UnexpectedAttribute(String),
+    MissingAttribute(&'static str),
+    MissingChild(&'static str),
+    MissingText,
+    InvalidValue { value: String, reason: String },
+    Xml(roxmltree::Error),
+    Io(String),
+}
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseErrorKind::UnexpectedElement(name) => write!(f, "unexpected element <{name}>"),
+            ParseErrorKind::UnexpectedNode(typ) => write!(f, "unexpected {typ:?} node"),
+            ParseErrorKind::UnexpectedAttribute(name) => write!(f, "unexpected attribute {name}"),
+            ParseErrorKind::MissingAttribute(name) => write!(f, "missing attribute {name}"),
+            ParseErrorKind::MissingChild(name) => write!(f, "missing child <{name}>"),
+            ParseErrorKind::MissingText => write!(f, "missing text"),
+            ParseErrorKind::InvalidValue { value, reason } => {
+                write!(f, "invalid value {value:?} ({reason})")
+            }
+            ParseErrorKind::Xml(e) => write!(f, "{e}"),
+            ParseErrorKind::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Abort on the first unexpected element, attribute or value
+    #[default]
+    Strict,
+    /// Skip unexpected elements, attributes and values, and record them in [`ParseContext::skipped`]
+    Lenient,
+}
+
+/// State shared by all parsers of one document: the parse mode and everything skipped so far.
+pub struct ParseContext {
+    pub mode:    ParseMode,
+    pub skipped: Vec<ParseError>,
+    // Errors are usually reported in document order, so we count lines incrementally from the last one.
+    // Keyed by the address of the input text, since streaming parses many small documents.
+    last_pos:    (usize, usize, TextPos),
+}
+impl ParseContext {
+    pub fn new(mode: ParseMode) -> Self {
+        ParseContext {
+            mode,
+            skipped: Vec::new(),
+            last_pos: (0, 0, TextPos::new(1, 1)),
+        }
+    }
+
+    pub fn error(&mut self, node: Node, kind: ParseErrorKind) -> ParseError {
+        let mut path = node
+            .ancestors()
+            .filter(|n| n.is_element())
+            .map(|n| n.tag_name().name())
+            .collect::<Vec<_>>();
+        path.reverse();
+
+        ParseError {
+            path: path.join("/"),
+            pos: self.text_pos(node.document().input_text(), node.range().start),
+            kind,
+        }
+    }
+
+    /// In strict mode this returns the error, in lenient mode it is recorded so the caller can skip the offending node.
+    pub fn recover(&mut self, error: ParseError) -> Result<(), ParseError> {
+        match self.mode {
+            ParseMode::Strict => Err(error),
+            ParseMode::Lenient => {
+                self.skipped.push(error);
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn unexpected(&mut self, node: Node) -> Result<(), ParseError> {
+        let kind = match node.node_type() {
+            NodeType::Element => ParseErrorKind::UnexpectedElement(node.tag_name().name().into()),
+            typ => ParseErrorKind::UnexpectedNode(typ),
+        };
+        let error = self.error(node, kind);
+        self.recover(error)
+    }
+
+    pub(crate) fn unexpected_attribute(
+        &mut self,
+        node: Node,
+        attrib: &roxmltree::Attribute,
+    ) -> Result<(), ParseError> {
+        let error = self.error(
+            node,
+            ParseErrorKind::UnexpectedAttribute(attrib.name().into()),
+        );
+        self.recover(error)
+    }
+
+    pub(crate) fn invalid(
+        &mut self,
+        node: Node,
+        value: &str,
+        reason: impl Display,
+    ) -> Result<(), ParseError> {
+        let error = self.error(node, ParseErrorKind::InvalidValue {
+            value:  value.into(),
+            reason: reason.to_string(),
+        });
+        self.recover(error)
+    }
+
+    pub(crate) fn text<'a>(&mut self, node: Node<'a, '_>) -> Result<Option<&'a str>, ParseError> {
+        match node.text() {
+            Some(text) => Ok(Some(text)),
+            None => {
+                let error = self.error(node, ParseErrorKind::MissingText);
+                self.recover(error).map(|_| None)
+            }
+        }
+    }
+
+    pub(crate) fn attribute<'a>(
+        &mut self,
+        node: Node<'a, '_>,
+        name: &'static str,
+    ) -> Result<Option<&'a str>, ParseError> {
+        match node.attribute(name) {
+            Some(value) => Ok(Some(value)),
+            None => {
+                let error = self.error(node, ParseErrorKind::MissingAttribute(name));
+                self.recover(error).map(|_| None)
+            }
+        }
+    }
+
+    pub(crate) fn parse_value<T, E: Display>(
+        &mut self,
+        node: Node,
+        value: &str,
+        parse: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, ParseError> {
+        match parse(value) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => self.invalid(node, value, e).map(|_| None),
+        }
+    }
+
+    pub(crate) fn parse_text_with<T, E: Display>(
+        &mut self,
+        node: Node,
+        parse: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, ParseError> {
+        match self.text(node)? {
+            Some(text) => self.parse_value(node, text, parse),
+            None => Ok(None),
+        }
+    }
+
+    pub(crate) fn parse_text<T>(&mut self, node: Node) -> Result<Option<T>, ParseError>
+    where
+        T: std::str::FromStr,
+        T::Err: Display,
+    {
+        self.parse_text_with(node, str::parse)
+    }
+
+    /// Report positions in `input` from `offset` onwards relative to `pos`, for documents that are part of a larger one.
+    pub(crate) fn set_origin(&mut self, input: &str, offset: usize, pos: TextPos) {
+        self.last_pos = (input.as_ptr() as usize, offset, pos);
+    }
+
+    fn text_pos(&mut self, input: &str, offset: usize) -> TextPos {
+        let (input_addr, mut from, mut pos) = self.last_pos;
+        if input_addr != input.as_ptr() as usize || offset < from {
+            (from, pos) = (0, TextPos::new(1, 1));
+        }
+
+        for c in input[from..offset].chars() {
+            if c == '\n' {
+                pos.row += 1;
+                pos.col = 1;
+            }
+            else {
+                pos.col += 1;
+            }
+        }
+
+        self.last_pos = (input.as_ptr() as usize, offset, pos);
+        pos
+    }
+}
@@ -0,0 +1,208 @@
+use compact_str::CompactString;
+use egui::ahash::HashMap;
+
+use crate::kana::strip_voicing;
+use crate::kanjidic2::Character;
+use crate::kanjivg::StrokeGroup;
+
+/// Kanji sharing a phonetic component (the `phon` attribute in KanjiVG), which usually hints at their on'yomi,
+/// e.g. 青 in 清, 晴, 精 and 請, which are all read セイ.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PhoneticSeries {
+    pub phonetic: char,
+    // The on'yomi shared by most members, empty if no two members share a reading
+    pub readings: Vec<CompactString>,
+    // The phonetic itself first if it is a kanji, then from most to least common
+    pub members:  Vec<SeriesMember>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SeriesMember {
+    pub kanji:     char,
+    pub onyomi:    Vec<CompactString>,
+    // None of the on'yomi match the readings of the series, ignoring voicing (so バン is a regular reading of ハン)
+    pub irregular: bool,
+}
+
+/// All phonetic series, with the kanji belonging to each.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct PhoneticIndex {
+    // Largest series first
+    series:   Vec<PhoneticSeries>,
+    // Indices into `series` for every member
+    by_kanji: HashMap<char, Vec<u32>>,
+}
+impl PhoneticIndex {
+    pub fn build(
+        kanji_strokes: &HashMap<char, StrokeGroup>,
+        kanji_dictionary: &HashMap<char, Character>,
+    ) -> Self {
+        let mut members: HashMap<char, Vec<char>> = HashMap::default();
+        for (&kanji, strokes) in kanji_strokes {
+            if !kanji_dictionary.contains_key(&kanji) {
+                continue;
+            }
+            let mut phonetics: Vec<char> = strokes.groups().filter_map(|g| g.phon).collect();
+            phonetics.sort_unstable();
+            phonetics.dedup();
+            for phonetic in phonetics {
+                members.entry(phonetic).or_default().push(kanji);
+            }
+        }
+
+        let mut series: Vec<PhoneticSeries> = members
+            .into_iter()
+            .map(|(phonetic, mut kanji)| {
+                if kanji_dictionary.contains_key(&phonetic) && !kanji.contains(&phonetic) {
+                    kanji.push(phonetic);
+                }
+                kanji.sort_unstable_by_key(|c| {
+                    let frequency = kanji_dictionary[c].misc.frequency;
+                    (*c != phonetic, frequency.unwrap_or(u16::MAX), *c)
+                });
+                PhoneticSeries::new(phonetic, &kanji, kanji_dictionary)
+            })
+            .filter(|series| series.members.len() > 1)
+            .collect();
+        series.sort_unstable_by_key(|s| (std::cmp::Reverse(s.members.len()), s.phonetic));
+
+        let mut by_kanji: HashMap<char, Vec<u32>> = HashMap::default();
+        for (idx, series) in series.iter().enumerate() {
+            for member in &series.members {
+                by_kanji.entry(member.kanji).or_default().push(idx as u32);
+            }
+        }
+
+        Self { series, by_kanji }
+    }
+
+    /// All series with at least two members, largest first.
+    pub fn series(&self) -> &[PhoneticSeries] { &self.series }
+
+    /// The series `kanji` belongs to, either as a member or as the phonetic itself.
+    pub fn series_of(&self, kanji: char) -> impl Iterator<Item = &PhoneticSeries> {
+        self.by_kanji
+            .get(&kanji)
+            .into_iter()
+            .flatten()
+            .map(|idx| &self.series[*idx as usize])
+    }
+}
+
+impl PhoneticSeries {
+    fn new(phonetic: char, kanji: &[char], kanji_dictionary: &HashMap<char, Character>) -> Self {
+        let mut members: Vec<SeriesMember> = kanji
+            .iter()
+            .map(|c| SeriesMember {
+                kanji:     *c,
+                onyomi:    kanji_dictionary[c]
+                    .onyomi()
+                    .into_iter()
+                    .map(CompactString::from)
+                    .collect(),
+                irregular: false,
+            })
+            .collect();
+
+        // Number of members with each reading, and its spelling in the first member that has it
+        let mut counts: Vec<(String, usize, &CompactString)> = Vec::new();
+        for member in &members {
+            let mut seen = Vec::new();
+            for reading in &member.onyomi {
+                let key = strip_voicing(reading);
+                if seen.contains(&key) {
+                    continue;
+                }
+                match counts.iter_mut().find(|(k, ..)| *k == key) {
+                    Some((_, count, _)) => *count += 1,
+                    None => counts.push((key.clone(), 1, reading)),
+                }
+                seen.push(key);
+            }
+        }
+
+        let max = counts.iter().map(|(_, count, _)| *count).max().unwrap_or(0);
+        let (keys, readings): (Vec<String>, Vec<CompactString>) = counts
+            .into_iter()
+            .filter(|(_, count, _)| max > 1 && *count == max)
+            .map(|(key, _, reading)| (key, reading.clone()))
+            .unzip();
+
+        if !keys.is_empty() {
+            for member in &mut members {
+                member.irregular = !member.onyomi.is_empty()
+                    && !member
+                        .onyomi
+                        .iter()
+                        .any(|r| keys.contains(&strip_voicing(r)));
+            }
+        }
+
+        Self {
+            phonetic,
+            readings,
+            members,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use egui::ahash::HashMap;
+
+    use super::PhoneticIndex;
+    use crate::kanjidic2::testing::character;
+    use crate::kanjidic2::{Character, ReadingType};
+    use crate::kanjivg::StrokeGroup;
+
+    fn onyomi(unicode: char, readings: &[&str]) -> Character {
+        character(unicode).readings(ReadingType::Onyomi(None), readings)
+    }
+
+    fn strokes(element: char, phon: Option<char>) -> StrokeGroup {
+        StrokeGroup {
+            element: Some(element),
+            subgroups: vec![StrokeGroup {
+                element: phon,
+                phon,
+                ..Default::default()
+            }
+            .into()],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_phonetic_series() {
+        let kanji_dictionary = HashMap::from_iter([
+            ('反', onyomi('反', &["ハン", "ホン", "タン"])),
+            ('板', onyomi('板', &["ハン", "バン"])),
+            ('飯', onyomi('飯', &["ハン"])),
+            ('坂', onyomi('坂', &["ハン"])),
+            ('仮', onyomi('仮', &["カ", "ケ"])),
+        ]);
+        let kanji_strokes = HashMap::from_iter([
+            ('反', strokes('反', None)),
+            ('板', strokes('板', Some('反'))),
+            ('飯', strokes('飯', Some('反'))),
+            ('坂', strokes('坂', Some('反'))),
+            ('仮', strokes('仮', Some('反'))),
+        ]);
+        let index = PhoneticIndex::build(&kanji_strokes, &kanji_dictionary);
+
+        assert_eq!(index.series().len(), 1);
+        let series = &index.series()[0];
+        assert_eq!(series.phonetic, '反');
+        assert_eq!(series.readings, ["ハン"]);
+        assert_eq!(series.members[0].kanji, '反');
+        let irregular: Vec<char> = series
+            .members
+            .iter()
+            .filter(|m| m.irregular)
+            .map(|m| m.kanji)
+            .collect();
+        assert_eq!(irregular, ['仮']);
+        assert_eq!(index.series_of('仮').count(), 1);
+        assert_eq!(index.series_of('口').count(), 0);
+    }
+}
@@ -0,0 +1,135 @@
+use egui::ahash::HashMap;
+
+use crate::jmdict;
+
+/// Inverted index from character n-grams to the entries containing them.
+/// Covers kanji, readings and glosses, so that [`crate::dictionary_search::search`] only has to rate entries which can contain the search term.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+pub struct SearchIndex {
+    // Sorted entry indices for every character
+    unigrams: HashMap<char, Vec<u32>>,
+    // Sorted entry indices for every pair of consecutive characters
+    bigrams:  HashMap<(char, char), Vec<u32>>,
+}
+impl SearchIndex {
+    pub fn build(entries: &[jmdict::Entry]) -> Self {
+        Self::build_from_texts(entries.iter().map(|entry| {
+            entry
+                .kanji
+                .iter()
+                .map(|k| k.text.as_str())
+                .chain(entry.reading.iter().map(|r| r.text.as_str()))
+                .chain(
+                    entry
+                        .sense
+                        .iter()
+                        .flat_map(|s| &s.glosses)
+                        .map(|g| g.text.as_str()),
+                )
+        }))
+    }
+
+    /// Build an index over arbitrary entries, given all searchable texts of each entry in order.
+    pub fn build_from_texts<'a, Texts>(entries: impl Iterator<Item = Texts>) -> Self
+    where
+        Texts: Iterator<Item = &'a str>,
+    {
+        fn insert<K: Eq + std::hash::Hash>(map: &mut HashMap<K, Vec<u32>>, key: K, idx: u32) {
+            let postings = map.entry(key).or_default();
+            // Entries are visited in order, so the postings stay sorted
+            if postings.last() != Some(&idx) {
+                postings.push(idx);
+            }
+        }
+
+        let mut index = SearchIndex::default();
+
+        for (idx, texts) in entries.enumerate() {
+            let idx = idx as u32;
+
+            for text in texts {
+                let mut prev = None;
+                for c in text.chars() {
+                    insert(&mut index.unigrams, c, idx);
+                    if let Some(prev) = prev {
+                        insert(&mut index.bigrams, (prev, c), idx);
+                    }
+                    prev = Some(c);
+                }
+            }
+        }
+
+        for postings in index.unigrams.values_mut() {
+            postings.shrink_to_fit();
+        }
+        for postings in index.bigrams.values_mut() {
+            postings.shrink_to_fit();
+        }
+
+        index
+    }
+
+    /// Append the indices of all entries that may contain `term` in a kanji, reading or gloss.
+    /// This is a superset of the actual matches, which still have to be checked.
+    pub fn candidates(&self, term: &str, result: &mut Vec<u32>) {
+        let chars: Vec<char> = term.chars().collect();
+
+        let mut postings: Vec<&Vec<u32>> = match chars.as_slice() {
+            [] => return,
+            [c] => self.unigrams.get(c).into_iter().collect(),
+            _ => {
+                let mut postings = Vec::new();
+                for pair in chars.windows(2) {
+                    let Some(p) = self.bigrams.get(&(pair[0], pair[1]))
+                    else {
+                        return;
+                    };
+                    postings.push(p);
+                }
+                postings
+            }
+        };
+        if postings.is_empty() {
+            return;
+        }
+
+        // Start with the rarest n-gram, so that we do as few lookups as possible
+        postings.sort_unstable_by_key(|p| p.len());
+        let (shortest, rest) = postings.split_first().unwrap();
+
+        result.extend(
+            shortest
+                .iter()
+                .filter(|idx| rest.iter().all(|p| p.binary_search(idx).is_ok())),
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SearchIndex;
+    use crate::jmdict::testing::entry;
+
+    #[test]
+    fn test_candidates() {
+        let entries = [
+            entry(1, "木", "き", "tree"),
+            entry(2, "木材", "もくざい", "wood"),
+            entry(3, "食べる", "たべる", "to eat"),
+        ];
+        let index = SearchIndex::build(&entries);
+
+        let candidates = |term: &str| {
+            let mut result = Vec::new();
+            index.candidates(term, &mut result);
+            result
+        };
+
+        assert_eq!(candidates("木"), [0, 1]);
+        assert_eq!(candidates("木材"), [1]);
+        assert_eq!(candidates("たべ"), [2]);
+        assert_eq!(candidates("eat"), [2]);
+        assert_eq!(candidates("tr"), [0]);
+        assert_eq!(candidates("xyz"), [] as [u32; 0]);
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use roxmltree::TextPos;
+
+use crate::parse_error::{ParseContext, ParseError, ParseErrorKind};
+
+/// Pull-based reader for documents that consist of a long list of small elements under the root, like JMdict or KANJIDIC2.
+/// Only one top-level element is kept in memory at a time, each of which is parsed into its own [`roxmltree::Document`].
+pub struct XmlStream<R> {
+    reader: R,
+    // Everything up to and including the root start tag, i.e. the XML declaration and the DTD
+    prolog: String,
+    root_start: String,
+    root_name: String,
+    root_pos: TextPos,
+    // Entities declared in the DTD, which have to be expanded before parsing an element on its own
+    entities: HashMap<String, String>,
+    pos: TextPos,
+    finished: bool,
+}
+impl<R: BufRead> XmlStream<R> {
+    /// Read the prolog and the root start tag.
+    pub fn new(reader: R) -> Result<Self, ParseError> {
+        let mut stream = XmlStream {
+            reader,
+            prolog: String::new(),
+            root_start: String::new(),
+            root_name: String::new(),
+            root_pos: TextPos::new(1, 1),
+            entities: HashMap::new(),
+            pos: TextPos::new(1, 1),
+            finished: false,
+        };
+
+        let mut bytes = Vec::new();
+        loop {
+            let Some((start, pos)) = stream
+                .read_markup(&mut bytes)
+                .map_err(|e| stream.io_error(e))?
+            else {
+                return Err(stream.error(stream.pos, ParseErrorKind::MissingChild("root")));
+            };
+            if !bytes[start..].starts_with(b"<?") && !bytes[start..].starts_with(b"<!") {
+                stream.root_pos = pos;
+                stream.finished = bytes.ends_with(b"/>");
+                stream.root_name = String::from_utf8_lossy(tag_name(&bytes[start..])).into();
+                stream.root_start = String::from_utf8_lossy(&bytes[start..]).into();
+                break;
+            }
+        }
+        stream.prolog = String::from_utf8(bytes).map_err(|e| stream.io_error(e))?;
+        stream.entities = entity_declarations(&stream.prolog)
+            .map(|(name, value)| (name.into(), value.into()))
+            .collect();
+
+        Ok(stream)
+    }
+
+    /// The XML declaration, DTD and root start tag.
+    pub fn prolog(&self) -> &str { &self.prolog }
+
+    pub fn root_name(&self) -> &str { &self.root_name }
+
+    /// Fail unless the root element is called `name`.
+    pub fn expect_root(&self, name: &str) -> Result<(), ParseError> {
+        match self.root_name == name {
+            true => Ok(()),
+            false => Err(self.error(
+                self.root_pos,
+                ParseErrorKind::UnexpectedElement(self.root_name.clone()),
+            )),
+        }
+    }
+
+    /// Read the next child element of the root into `buf`, skipping text, comments and processing instructions in between.
+    /// Returns the position where the element starts, or `None` once the root element is closed.
+    pub fn next_element(&mut self, buf: &mut String) -> std::io::Result<Option<TextPos>> {
+        let mut bytes = std::mem::take(buf).into_bytes();
+        bytes.clear();
+
+        let mut depth = 0_u32;
+        let mut element_pos = None;
+        while !self.finished {
+            let Some((start, pos)) = self.read_markup(&mut bytes)?
+            else {
+                if depth > 0 {
+                    return Err(std::io::ErrorKind::UnexpectedEof.into());
+                }
+                self.finished = true;
+                break;
+            };
+            let start = match depth {
+                // Drop the text between elements
+                0 => {
+                    bytes.drain(..start);
+                    0
+                }
+                _ => start,
+            };
+
+            let markup = &bytes[start..];
+            if markup.starts_with(b"</") {
+                if depth == 0 {
+                    // End of the root element
+                    self.finished = true;
+                    break;
+                }
+                depth -= 1;
+            }
+            else if markup.starts_with(b"<!") || markup.starts_with(b"<?") {
+                if depth == 0 {
+                    bytes.clear();
+                }
+                continue;
+            }
+            else if !markup.ends_with(b"/>") {
+                depth += 1;
+            }
+
+            if element_pos.is_none() {
+                element_pos = Some(pos);
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+
+        *buf = String::from_utf8(bytes)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(element_pos)
+    }
+
+    /// Parse every child element of the root on its own and pass it to `parse`.
+    /// Errors are reported with their position in the whole document, and malformed elements are skipped in lenient mode.
+    pub fn for_each_element(
+        &mut self,
+        ctx: &mut ParseContext,
+        mut parse: impl FnMut(roxmltree::Node, &mut ParseContext) -> Result<(), ParseError>,
+    ) -> Result<(), ParseError> {
+        let mut element = String::new();
+        let mut input = String::new();
+
+        while let Some(pos) = self
+            .next_element(&mut element)
+            .map_err(|e| self.io_error(e))?
+        {
+            // Wrap the element in the root, so that namespaces and element paths stay the same
+            input.clear();
+            input.push_str(&self.root_start);
+            expand_entities(&element, &self.entities, &mut input);
+            input.push_str("</");
+            input.push_str(&self.root_name);
+            input.push('>');
+
+            let document = match roxmltree::Document::parse(&input) {
+                Ok(document) => document,
+                Err(e) => {
+                    ctx.recover(self.error(pos, ParseErrorKind::Xml(e)))?;
+                    continue;
+                }
+            };
+            ctx.set_origin(&input, self.root_start.len(), pos);
+
+            for node in document.root_element().children() {
+                if node.is_element() {
+                    parse(node, ctx)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Append text up to and including the next complete piece of markup (tag, comment, processing instruction, CDATA or DTD) to `out`.
+    /// Returns where the markup starts in `out` and in the document, or `None` at the end of the input.
+    fn read_markup(&mut self, out: &mut Vec<u8>) -> std::io::Result<Option<(usize, TextPos)>> {
+        let text_start = out.len();
+        self.reader.read_until(b'<', out)?;
+        if out.last() != Some(&b'<') || out.len() == text_start {
+            self.advance(text_start, out);
+            return Ok(None);
+        }
+
+        let start = out.len() - 1;
+        self.advance(text_start, &out[..start]);
+        let pos = self.pos;
+
+        loop {
+            if self.reader.read_until(b'>', out)? == 0 || out.last() != Some(&b'>') {
+                return Err(std::io::ErrorKind::UnexpectedEof.into());
+            }
+            if markup_complete(&out[start..]) {
+                break;
+            }
+        }
+        self.advance(start, out);
+
+        Ok(Some((start, pos)))
+    }
+
+    fn advance(&mut self, from: usize, bytes: &[u8]) {
+        for &b in &bytes[from..] {
+            if b == b'\n' {
+                self.pos.row += 1;
+                self.pos.col = 1;
+            }
+            // Count characters, not UTF-8 continuation bytes
+            else if b & 0xC0 != 0x80 {
+                self.pos.col += 1;
+            }
+        }
+    }
+
+    fn error(&self, pos: TextPos, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            path: self.root_name.clone(),
+            pos,
+            kind,
+        }
+    }
+
+    fn io_error(&self, e: impl std::fmt::Display) -> ParseError {
+        self.error(self.pos, ParseErrorKind::Io(e.to_string()))
+    }
+}
+
+/// Iterate over all `<!ENTITY name "value">` declarations in a DTD.
+pub(crate) fn entity_declarations(input: &str) -> impl Iterator<Item = (&str, &str)> {
+    let mut rest = input;
+    std::iter::from_fn(move || loop {
+        let start = rest.find("<!ENTITY")?;
+        rest = &rest[start + "<!ENTITY".len()..];
+
+        let declaration = rest.trim_start();
+        let (name, value) = declaration.split_once(char::is_whitespace)?;
+        let value = value.trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'')
+        else {
+            continue;
+        };
+        let (value, _) = value[1..].split_once(quote)?;
+
+        return Some((name, value));
+    })
+}
+
+// Replace references to entities declared in the DTD with their value, like the parser would have done with the whole document
+fn expand_entities(text: &str, entities: &HashMap<String, String>, out: &mut String) {
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest
+            .find(';')
+            .and_then(|end| Some((end, entities.get(&rest[1..end])?)));
+        match value {
+            Some((end, value)) => {
+                out.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            // Predefined or character reference
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+}
+
+fn markup_complete(markup: &[u8]) -> bool {
+    if markup.starts_with(b"<!--") {
+        markup.len() >= 7 && markup.ends_with(b"-->")
+    }
+    else if markup.starts_with(b"<![CDATA[") {
+        markup.ends_with(b"]]>")
+    }
+    else if markup.starts_with(b"<?") {
+        markup.ends_with(b"?>")
+    }
+    else if markup.starts_with(b"<!") {
+        // <!DOCTYPE name [ internal subset ]>
+        match markup.contains(&b'[') {
+            true => markup[..markup.len() - 1].trim_ascii_end().ends_with(b"]"),
+            false => true,
+        }
+    }
+    else {
+        // A tag is only complete if the > isn't part of an attribute value
+        let mut quote = None;
+        for &b in markup {
+            match quote {
+                Some(q) if b == q => quote = None,
+                None if b == b'"' || b == b'\'' => quote = Some(b),
+                _ => (),
+            }
+        }
+        quote.is_none()
+    }
+}
+
+fn tag_name(markup: &[u8]) -> &[u8] {
+    let name = &markup[1..];
+    let end = name
+        .iter()
+        .position(|b| b.is_ascii_whitespace() || *b == b'/' || *b == b'>')
+        .unwrap_or(name.len());
+    &name[..end]
+}
+
+#[cfg(test)]
+mod test {
+    use super::XmlStream;
+
+    #[test]
+    fn test_next_element() {
+        let xml = r#"<?xml version="1.0"?>
+<!DOCTYPE root [
+<!ENTITY e "expanded">
+<!-- a comment that isn't > the end -->
+]>
+<root xmlns:x="urn:x">
+<!-- <a>not an element</a> -->
+<a x:attr="1 > 0"><b/>text</a>
+<c/>  <a><a>nested</a></a>
+</root>"#;
+        let mut stream = XmlStream::new(xml.as_bytes()).unwrap();
+        assert_eq!(stream.root_name(), "root");
+        stream.expect_root("root").unwrap();
+
+        let mut element = String::new();
+        let mut next = || {
+            let pos = stream.next_element(&mut element).unwrap()?;
+            Some((element.clone(), pos.row, pos.col))
+        };
+        assert_eq!(
+            next(),
+            Some((r#"<a x:attr="1 > 0"><b/>text</a>"#.into(), 8, 1))
+        );
+        assert_eq!(next(), Some(("<c/>".into(), 9, 1)));
+        assert_eq!(next(), Some(("<a><a>nested</a></a>".into(), 9, 7)));
+        assert_eq!(next(), None);
+        assert_eq!(next(), None);
+    }
+}